@@ -0,0 +1,101 @@
+//! Contains the actions that input can perform on a typing test
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+
+/// Something that can happen to a running typing test
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    /// Quit the test early
+    Quit,
+    /// Exit the program right away without a summary or saving the test
+    Interrupt,
+    /// Restart the test
+    Reset,
+    /// Undo one typed character
+    Backspace,
+    /// Undo the current word
+    BackspaceWord,
+    /// Pause or resume the timer
+    Pause,
+    /// Skip the current line without credit for its words
+    Skip,
+    /// Confirm a choice such as leaving the summary screen
+    Enter,
+    /// Enter a character into the test
+    Type(char),
+    /// The terminal was resized to a number of columns and rows
+    Resize(u16, u16),
+    /// Time passed without any input
+    Tick,
+}
+
+impl Action {
+    /// Get the action a terminal event performs if there is one
+    pub fn from_event(event: Event) -> Option<Self> {
+        match event {
+            Event::Resize(columns, rows) => Some(Action::Resize(columns, rows)),
+            Event::Key(key) => match key.code {
+                KeyCode::Esc => Some(Action::Quit),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(Action::Interrupt)
+                }
+                KeyCode::Tab => Some(Action::Reset),
+                KeyCode::F(2) => Some(Action::Pause),
+                KeyCode::F(3) => Some(Action::Skip),
+                KeyCode::Enter => Some(Action::Enter),
+                KeyCode::Backspace if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(Action::BackspaceWord)
+                }
+                KeyCode::Backspace => Some(Action::Backspace),
+                KeyCode::Char(ch) => Some(Action::Type(ch)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEvent;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn action_from_event_test() {
+        assert_eq!(Action::from_event(key(KeyCode::Esc)), Some(Action::Quit));
+        assert_eq!(
+            Action::from_event(Event::Key(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL
+            ))),
+            Some(Action::Interrupt)
+        );
+        assert_eq!(Action::from_event(key(KeyCode::Tab)), Some(Action::Reset));
+        assert_eq!(Action::from_event(key(KeyCode::F(2))), Some(Action::Pause));
+        assert_eq!(Action::from_event(key(KeyCode::F(3))), Some(Action::Skip));
+        assert_eq!(Action::from_event(key(KeyCode::Enter)), Some(Action::Enter));
+        assert_eq!(
+            Action::from_event(key(KeyCode::Backspace)),
+            Some(Action::Backspace)
+        );
+        assert_eq!(
+            Action::from_event(Event::Key(KeyEvent::new(
+                KeyCode::Backspace,
+                KeyModifiers::CONTROL
+            ))),
+            Some(Action::BackspaceWord)
+        );
+        assert_eq!(
+            Action::from_event(key(KeyCode::Char('a'))),
+            Some(Action::Type('a'))
+        );
+        assert_eq!(Action::from_event(key(KeyCode::Up)), None);
+        assert_eq!(
+            Action::from_event(Event::Resize(80, 24)),
+            Some(Action::Resize(80, 24))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Used to change the shape of the caret at the typing position
+use crossterm::Command;
+use std::fmt;
+use std::str::FromStr;
+
+/// The shapes the caret can be drawn as
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaretStyle {
+    /// Whatever shape the terminal uses by default
+    Default,
+    /// A blinking block covering the next character
+    Block,
+    /// A blinking line under the next character
+    Underline,
+    /// A blinking line before the next character
+    Bar,
+}
+
+impl CaretStyle {
+    /// Get the parameter of the escape sequence that sets the style
+    fn code(self) -> u8 {
+        match self {
+            CaretStyle::Default => 0,
+            CaretStyle::Block => 1,
+            CaretStyle::Underline => 3,
+            CaretStyle::Bar => 5,
+        }
+    }
+}
+
+impl FromStr for CaretStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(CaretStyle::Block),
+            "underline" => Ok(CaretStyle::Underline),
+            "bar" => Ok(CaretStyle::Bar),
+            _ => Err(format!(
+                "Invalid caret \"{s}\". Use \"block\", \"underline\", or \"bar\"."
+            )),
+        }
+    }
+}
+
+/// A command that sets the caret style
+/// crossterm 0.22 has no command for this so the escape sequence is written directly
+pub struct SetCaret(pub CaretStyle);
+
+impl Command for SetCaret {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b[{} q", self.0.code())
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crossterm::Result<()> {
+        // the caret shape cannot be changed without escape sequences
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caret_style_from_str_test() {
+        assert_eq!("block".parse(), Ok(CaretStyle::Block));
+        assert_eq!("underline".parse(), Ok(CaretStyle::Underline));
+        assert_eq!("bar".parse(), Ok(CaretStyle::Bar));
+        let e = "beam".parse::<CaretStyle>().unwrap_err();
+        assert!(e.contains("\"beam\""), "{}", e);
+        assert!("Block".parse::<CaretStyle>().is_err());
+    }
+
+    #[test]
+    fn set_caret_test() {
+        let mut ansi = String::new();
+        SetCaret(CaretStyle::Bar).write_ansi(&mut ansi).unwrap();
+        assert_eq!(ansi, "\x1b[5 q");
+        let mut ansi = String::new();
+        SetCaret(CaretStyle::Default).write_ansi(&mut ansi).unwrap();
+        assert_eq!(ansi, "\x1b[0 q");
+    }
+}
@@ -0,0 +1,210 @@
+//! Used to read default settings from a config file
+use crate::line::{Theme, WpmTiers};
+use crate::Args;
+use crossterm::style::Color;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Get the path of the config file in the user's config directory
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("typing_test").join("config.toml"))
+}
+
+/// Colors given as red, green, and blue values
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    /// Color of correctly typed characters
+    pub completed: Option<[u8; 3]>,
+    /// Color of characters that have not been typed yet
+    pub uncompleted: Option<[u8; 3]>,
+    /// Color of incorrectly typed characters
+    pub error: Option<[u8; 3]>,
+}
+
+/// The wpm that separate slow, average, and fast typing speeds
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tiers {
+    /// Anything slower than this is drawn in red
+    pub slow: Option<f32>,
+    /// Anything faster than this is drawn in green
+    pub fast: Option<f32>,
+}
+
+/// Default settings that are used unless overridden by command-line arguments
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The number of words to type before a test ends
+    pub number: Option<u32>,
+    /// The number of seconds a test lasts
+    pub time: Option<u64>,
+    /// Colors used to draw lines
+    pub colors: Colors,
+    /// The wpm the current wpm changes color at
+    pub wpm_tiers: Tiers,
+}
+
+impl Config {
+    /// Parse a config from the contents of a config file
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let config: Self =
+            toml::from_str(contents).map_err(|e| format!("Invalid config file because {e}."))?;
+        crate::validate_limits(config.number, config.time)
+            .map_err(|e| format!("Invalid config file. {e}"))?;
+        Ok(config)
+    }
+
+    /// Read the config file
+    /// Returns the default config if the file does not exist
+    pub fn load() -> Result<Self, String> {
+        let path = match path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!(
+                "Could not read config file \"{}\" because {e}.",
+                path.display()
+            )),
+        }
+    }
+
+    /// Get the colors to draw lines with, using the default for any color not set
+    pub fn theme(&self) -> Theme {
+        let rgb = |color: Option<[u8; 3]>, default| match color {
+            Some([r, g, b]) => Color::Rgb { r, g, b },
+            None => default,
+        };
+        let default = Theme::default();
+        Theme {
+            completed: rgb(self.colors.completed, default.completed),
+            uncompleted: rgb(self.colors.uncompleted, default.uncompleted),
+            error: rgb(self.colors.error, default.error),
+            labels: default.labels,
+        }
+    }
+
+    /// Get the wpm tiers, using the defaults for any that are not set
+    pub fn wpm_tiers(&self) -> WpmTiers {
+        let default = WpmTiers::default();
+        WpmTiers {
+            slow: self.wpm_tiers.slow.unwrap_or(default.slow),
+            fast: self.wpm_tiers.fast.unwrap_or(default.fast),
+        }
+    }
+
+    /// Fill in anything not given on the command line with the settings from the config
+    pub fn apply(&self, args: &mut Args) {
+        let mode_given = args.number.is_some()
+            || args.time.is_some()
+            || args.quote
+            || args.zen
+            || args.endless_quotes
+            || args.code
+            || args.session.is_some();
+        if !mode_given {
+            args.number = self.number;
+            args.time = self.time;
+        }
+        let theme = args.named_theme.unwrap_or_else(|| self.theme());
+        args.theme = Theme {
+            completed: args.correct_color.unwrap_or(theme.completed),
+            uncompleted: args.pending_color.unwrap_or(theme.uncompleted),
+            error: args.error_color.unwrap_or(theme.error),
+            labels: theme.labels,
+        };
+        args.wpm_tiers = self.wpm_tiers();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use structopt::StructOpt;
+
+    #[test]
+    fn config_parse_test() {
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+        let config = Config::parse("number = 50\n[colors]\nerror = [255, 0, 0]\n").unwrap();
+        assert_eq!(config.number, Some(50));
+        assert_eq!(config.colors.error, Some([255, 0, 0]));
+        assert!(Config::parse("number = \"fifty\"").is_err());
+        assert!(Config::parse("not_a_setting = 1").is_err());
+        assert!(Config::parse("number = 0").is_err());
+        assert!(Config::parse("time = 0").is_err());
+    }
+
+    #[test]
+    fn config_theme_test() {
+        let config =
+            Config::parse("[colors]\ncompleted = [0, 255, 0]\nuncompleted = [10, 20, 30]\n")
+                .unwrap();
+        let theme = config.theme();
+        assert_eq!(theme.completed, Color::Rgb { r: 0, g: 255, b: 0 });
+        assert_eq!(
+            theme.uncompleted,
+            Color::Rgb {
+                r: 10,
+                g: 20,
+                b: 30
+            }
+        );
+        assert_eq!(theme.error, Theme::default().error);
+        assert_eq!(Config::default().theme(), Theme::default());
+    }
+
+    #[test]
+    fn config_wpm_tiers_test() {
+        let config = Config::parse("[wpm_tiers]\nfast = 100\n").unwrap();
+        assert_eq!(
+            config.wpm_tiers(),
+            WpmTiers {
+                slow: 40.0,
+                fast: 100.0
+            }
+        );
+        assert_eq!(Config::default().wpm_tiers(), WpmTiers::default());
+        assert!(Config::parse("[wpm_tiers]\nmedium = 60\n").is_err());
+    }
+
+    #[test]
+    fn config_apply_test() {
+        let config = Config::parse("time = 60\n[colors]\nerror = [1, 2, 3]").unwrap();
+        let mut args = Args::from_iter(["typing_test"]);
+        config.apply(&mut args);
+        assert_eq!(args.time, Some(60));
+        assert_eq!(args.theme.error, Color::Rgb { r: 1, g: 2, b: 3 });
+        // the command line overrides the config
+        let mut args = Args::from_iter(["typing_test", "-n", "10"]);
+        config.apply(&mut args);
+        assert_eq!(args.time, None);
+        assert_eq!(args.number, Some(10));
+        let config = Config::parse("time = 60\nnumber = 50").unwrap();
+        for mode in ["--endless-quotes", "--code"] {
+            let mut args = Args::from_iter(["typing_test", mode]);
+            config.apply(&mut args);
+            assert_eq!(args.time, None, "{}", mode);
+            assert_eq!(args.number, None, "{}", mode);
+        }
+        let mut args = Args::from_iter(["typing_test", "--error-color", "#0000ff"]);
+        config.apply(&mut args);
+        assert_eq!(args.theme.error, Color::Rgb { r: 0, g: 0, b: 255 });
+        // a named theme replaces the config colors and individual colors override it
+        let monokai = Theme::named("monokai").unwrap();
+        let mut args = Args::from_iter(["typing_test", "--theme", "monokai"]);
+        config.apply(&mut args);
+        assert_eq!(args.theme, monokai);
+        let mut args =
+            Args::from_iter(["typing_test", "--theme", "monokai", "--error-color", "red"]);
+        config.apply(&mut args);
+        assert_eq!(args.theme.error, Color::Red);
+        assert_eq!(args.theme.completed, monokai.completed);
+        assert!(Args::from_iter_safe(["typing_test", "--theme", "neon"]).is_err());
+    }
+}
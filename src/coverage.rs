@@ -0,0 +1,74 @@
+//! Keeps track of which words of the word list have been seen across sessions
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The name of the file the seen words are stored in
+const FILE_NAME: &str = "coverage.json";
+
+/// The distinct words that have appeared in tests
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Coverage {
+    seen: HashSet<String>,
+}
+
+impl Coverage {
+    /// Load the words seen in previous sessions
+    /// Returns a message describing the problem if the saved file is invalid
+    pub fn load() -> Result<Self, String> {
+        crate::storage::load(FILE_NAME)
+    }
+
+    /// Save the words seen for future sessions
+    pub fn save(&self) -> std::io::Result<()> {
+        crate::storage::save(FILE_NAME, self)
+    }
+
+    /// Mark every word in a line as seen
+    pub fn record(&mut self, line: &str) {
+        self.seen
+            .extend(line.split(' ').filter(|w| !w.is_empty()).map(String::from));
+    }
+
+    /// Calculate the percentage of the distinct words of a word list that have been seen
+    pub fn percent<S: AsRef<str>>(&self, words: &[S]) -> f32 {
+        let words: HashSet<&str> = words.iter().map(AsRef::as_ref).collect();
+        if words.is_empty() {
+            return 0f32;
+        }
+        let seen = words
+            .iter()
+            .filter(|&&word| self.seen.contains(word))
+            .count();
+        seen as f32 / words.len() as f32 * 100f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage_record_test() {
+        let mut coverage = Coverage::default();
+        coverage.record("a b a");
+        coverage.record("c");
+        coverage.record("");
+        assert_eq!(
+            coverage.seen,
+            HashSet::from(["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+    }
+
+    #[test]
+    fn coverage_percent_test() {
+        let mut coverage = Coverage::default();
+        assert_eq!(coverage.percent(&["a", "b", "c", "d"]), 0.0);
+        coverage.record("a b not_in_list");
+        assert_eq!(coverage.percent(&["a", "b", "c", "d"]), 50.0);
+        assert_eq!(coverage.percent::<&str>(&[]), 0.0);
+        // repeated words in the list are only counted once
+        assert_eq!(coverage.percent(&["a", "a", "a", "c"]), 50.0);
+        let words = vec!["b".to_string(), "e".to_string()];
+        assert_eq!(coverage.percent(&words), 50.0);
+    }
+}
@@ -0,0 +1,78 @@
+//! Used to log the results of finished tests to a file
+use crate::stats::TestResult;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One row of the results log
+#[derive(Debug, Serialize)]
+struct Row<'a> {
+    /// Seconds since the unix epoch when the test was logged
+    timestamp: u64,
+    mode: &'a str,
+    words: u32,
+    wpm: Option<f32>,
+    accuracy: Option<f32>,
+}
+
+/// Get the number of seconds since the unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Append a row for each result to a csv file
+/// The file is created with a header row if it does not exist
+pub fn append_csv(path: &Path, results: &[TestResult], timestamp: u64) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let is_new = file.metadata()?.len() == 0;
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(is_new)
+        .from_writer(file);
+    for result in results {
+        writer.serialize(Row {
+            timestamp,
+            mode: &result.mode,
+            words: result.words,
+            wpm: result.wpm(),
+            accuracy: result.accuracy(),
+        })?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(mode: &str, words: u32) -> TestResult {
+        TestResult {
+            mode: mode.into(),
+            words,
+            raw_words: words,
+            elapsed: 60.0,
+            keystrokes: 100,
+            errors: 10,
+        }
+    }
+
+    #[test]
+    fn append_csv_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.csv");
+        append_csv(&path, &[result("30 words", 30)], 100).unwrap();
+        append_csv(&path, &[result("quote", 45)], 200).unwrap();
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["timestamp", "mode", "words", "wpm", "accuracy"]
+        );
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], vec!["100", "30 words", "30", "30.0", "90.0"]);
+        assert_eq!(rows[1], vec!["200", "quote", "45", "45.0", "90.0"]);
+    }
+}
@@ -0,0 +1,226 @@
+//! Keeps the results of every finished test across sessions
+use crate::stats::{average, trend, Average, TestResult};
+use serde::{Deserialize, Serialize};
+
+/// The name of the file the results are stored in
+const FILE_NAME: &str = "history.json";
+/// The number of results shown on the leaderboard
+pub const LEADERBOARD_LEN: usize = 10;
+
+/// A finished test along with when it finished
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// Seconds since the unix epoch when the test finished
+    pub timestamp: u64,
+    /// How the test went
+    pub result: TestResult,
+}
+
+/// Statistics about every saved result
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    /// The number of saved results
+    pub tests: usize,
+    /// The mean wpm and accuracy of every result
+    pub average: Average,
+    /// The highest wpm of any result
+    pub best: Option<f32>,
+    /// The change in wpm over the most recent results
+    pub trend: Option<f32>,
+}
+
+/// Every result that has been saved
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    records: Vec<Record>,
+}
+
+impl History {
+    /// Load the results of previous sessions
+    /// Returns a message describing the problem if the saved file is invalid
+    pub fn load() -> Result<Self, String> {
+        crate::storage::load(FILE_NAME)
+    }
+
+    /// Save the results for future sessions
+    pub fn save(&self) -> std::io::Result<()> {
+        crate::storage::save(FILE_NAME, self)
+    }
+
+    /// Add results that finished at the given time
+    pub fn record(&mut self, results: &[TestResult], timestamp: u64) {
+        self.records.extend(
+            results
+                .iter()
+                .cloned()
+                .map(|result| Record { timestamp, result }),
+        );
+    }
+
+    /// Get the highest wpm of any record of {mode}
+    pub fn best_wpm(&self, mode: &str) -> Option<f32> {
+        rank(&self.records, Some(mode), 1)
+            .first()
+            .and_then(|record| record.result.wpm())
+    }
+
+    /// Summarize every record, measuring the trend over the last {recent} records
+    pub fn stats(&self, recent: usize) -> Stats {
+        let results: Vec<TestResult> = self.records.iter().map(|r| r.result.clone()).collect();
+        Stats {
+            tests: results.len(),
+            average: average(&results),
+            best: rank(&self.records, None, 1)
+                .first()
+                .and_then(|record| record.result.wpm()),
+            trend: trend(&results[results.len().saturating_sub(recent)..]),
+        }
+    }
+
+    /// Get the best {limit} records, only including those of {mode} if given
+    pub fn leaderboard(&self, mode: Option<&str>, limit: usize) -> Vec<&Record> {
+        rank(&self.records, mode, limit)
+    }
+}
+
+/// Whether {wpm} beats the personal best
+/// The first measurable result is not counted since there is nothing to beat
+pub fn is_new_best(best: Option<f32>, wpm: Option<f32>) -> bool {
+    matches!((best, wpm), (Some(best), Some(wpm)) if wpm > best)
+}
+
+/// Sort records from the highest to the lowest wpm
+/// Ties are broken by accuracy and then by the most recent
+/// Records too fast to measure are left out
+fn rank<'a>(records: &'a [Record], mode: Option<&str>, limit: usize) -> Vec<&'a Record> {
+    let mut ranked: Vec<&Record> = records
+        .iter()
+        .filter(|record| mode.is_none_or(|mode| record.result.mode == mode))
+        .filter(|record| record.result.wpm().is_some())
+        .collect();
+    let key = |record: &Record| {
+        (
+            record.result.wpm().unwrap_or_default(),
+            record.result.accuracy().unwrap_or_default(),
+        )
+    };
+    ranked.sort_by(|a, b| {
+        let (a_wpm, a_accuracy) = key(a);
+        let (b_wpm, b_accuracy) = key(b);
+        b_wpm
+            .total_cmp(&a_wpm)
+            .then(b_accuracy.total_cmp(&a_accuracy))
+            .then(b.timestamp.cmp(&a.timestamp))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(mode: &str, words: u32, errors: u32, timestamp: u64) -> Record {
+        Record {
+            timestamp,
+            result: TestResult {
+                mode: mode.into(),
+                words,
+                raw_words: words,
+                elapsed: 60.0,
+                keystrokes: 100,
+                errors,
+            },
+        }
+    }
+
+    #[test]
+    fn rank_test() {
+        let records = [
+            record("quote", 50, 0, 1),
+            record("30 words", 70, 5, 2),
+            // same wpm as the last but less accurate
+            record("30 words", 70, 10, 3),
+            // same wpm and accuracy as the second but more recent
+            record("quote", 70, 5, 4),
+            record("quote", 90, 0, 5),
+        ];
+        let timestamps =
+            |ranked: Vec<&Record>| ranked.iter().map(|r| r.timestamp).collect::<Vec<_>>();
+        assert_eq!(timestamps(rank(&records, None, 10)), [5, 4, 2, 3, 1]);
+        assert_eq!(timestamps(rank(&records, None, 2)), [5, 4]);
+        assert_eq!(timestamps(rank(&records, Some("quote"), 10)), [5, 4, 1]);
+        assert!(rank(&records, Some("zen"), 10).is_empty());
+        assert!(rank(&[], None, 10).is_empty());
+    }
+
+    #[test]
+    fn rank_too_fast_test() {
+        let mut too_fast = record("quote", 10, 0, 1);
+        too_fast.result.elapsed = 0.01;
+        let records = [too_fast, record("quote", 10, 0, 2)];
+        assert_eq!(rank(&records, None, 10), [&records[1]]);
+    }
+
+    #[test]
+    fn best_wpm_test() {
+        let mut history = History::default();
+        assert_eq!(history.best_wpm("quote"), None);
+        history.record(&[record("quote", 50, 0, 1).result], 1);
+        history.record(&[record("30 words", 80, 0, 2).result], 2);
+        assert_eq!(history.best_wpm("quote"), Some(50.0));
+        // a faster result replaces the personal best
+        history.record(&[record("quote", 60, 0, 3).result], 3);
+        assert_eq!(history.best_wpm("quote"), Some(60.0));
+        history.record(&[record("quote", 55, 0, 4).result], 4);
+        assert_eq!(history.best_wpm("quote"), Some(60.0));
+    }
+
+    #[test]
+    fn is_new_best_test() {
+        assert!(is_new_best(Some(80.0), Some(80.5)));
+        assert!(!is_new_best(Some(80.0), Some(80.0)));
+        assert!(!is_new_best(Some(80.0), Some(70.0)));
+        assert!(!is_new_best(None, Some(70.0)));
+        assert!(!is_new_best(Some(80.0), None));
+    }
+
+    #[test]
+    fn stats_test() {
+        let stats = History::default().stats(10);
+        assert_eq!(stats.tests, 0);
+        assert_eq!(stats.average.wpm, None);
+        assert_eq!(stats.best, None);
+        assert_eq!(stats.trend, None);
+        let mut history = History::default();
+        for (i, (mode, words, errors)) in [
+            ("quote", 40, 0),
+            ("30 words", 60, 25),
+            ("quote", 50, 0),
+            ("30 words", 70, 5),
+            ("quote", 80, 10),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            history.record(&[record(mode, words, errors, i as u64).result], i as u64);
+        }
+        let stats = history.stats(10);
+        assert_eq!(stats.tests, 5);
+        assert_eq!(stats.average.wpm, Some(60.0));
+        assert_eq!(stats.average.accuracy, Some(92.0));
+        assert_eq!(stats.best, Some(80.0));
+        assert!(stats.trend.is_some_and(|trend| trend > 0.0));
+        // only the most recent records count towards the trend
+        assert_eq!(history.stats(4).trend, Some(20.0));
+        assert_eq!(history.stats(2).trend, Some(10.0));
+        assert_eq!(history.stats(1).trend, None);
+    }
+
+    #[test]
+    fn history_record_test() {
+        let mut history = History::default();
+        history.record(&[record("quote", 10, 0, 0).result], 42);
+        assert_eq!(history.leaderboard(None, 10)[0].timestamp, 42);
+    }
+}
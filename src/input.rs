@@ -0,0 +1,86 @@
+//! Contains the sources that keyboard input can be read from
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for an event before letting the test update without one
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Somewhere key presses and other terminal events come from
+pub trait InputSource {
+    /// Wait a short time for the next event
+    /// Returns None if nothing happened
+    fn poll(&mut self) -> crossterm::Result<Option<Event>>;
+}
+
+/// Read events from the terminal
+pub struct TerminalInput;
+
+impl InputSource for TerminalInput {
+    fn poll(&mut self) -> crossterm::Result<Option<Event>> {
+        if event::poll(POLL_INTERVAL)? {
+            event::read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Send a fixed sequence of events and then press Esc once they run out
+/// Quitting at the end keeps a test from waiting forever for more input
+pub struct ScriptedInput {
+    events: VecDeque<Event>,
+    delay: Duration,
+}
+
+impl ScriptedInput {
+    /// Send each of the given events in order
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Wait {delay} before each event like a person typing at a steady pace
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+
+    /// Type each character of {text} in order
+    pub fn from_text(text: &str) -> Self {
+        Self::new(text.chars().map(|ch| key(KeyCode::Char(ch))))
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn poll(&mut self) -> crossterm::Result<Option<Event>> {
+        thread::sleep(self.delay);
+        Ok(Some(
+            self.events.pop_front().unwrap_or_else(|| key(KeyCode::Esc)),
+        ))
+    }
+}
+
+/// Make the event of pressing a key without modifiers
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_input_test() {
+        let mut input = ScriptedInput::new([key(KeyCode::Char('a')), Event::Resize(40, 10)]);
+        assert_eq!(input.poll().unwrap(), Some(key(KeyCode::Char('a'))));
+        assert_eq!(input.poll().unwrap(), Some(Event::Resize(40, 10)));
+        assert_eq!(input.poll().unwrap(), Some(key(KeyCode::Esc)));
+        assert_eq!(input.poll().unwrap(), Some(key(KeyCode::Esc)));
+        let mut input = ScriptedInput::from_text("ab");
+        assert_eq!(input.poll().unwrap(), Some(key(KeyCode::Char('a'))));
+        assert_eq!(input.poll().unwrap(), Some(key(KeyCode::Char('b'))));
+    }
+}
@@ -0,0 +1,113 @@
+//! Renders a keyboard where each key is colored by how often it was mistyped
+use crossterm::style::{Color, Stylize};
+use std::collections::HashMap;
+
+/// The rows of keys on a keyboard
+const ROWS: [&str; 4] = ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"];
+/// The color of keys that were never mistyped
+const UNTOUCHED: Color = Color::Rgb {
+    r: 60,
+    g: 60,
+    b: 60,
+};
+
+/// Get the color of a key given its number of errors and the most errors of any key
+/// Keys go from dark red to bright red as they approach the most errors
+pub fn key_color(errors: u32, max: u32) -> Color {
+    if errors == 0 || max == 0 {
+        return UNTOUCHED;
+    }
+    let heat = errors.min(max) as f32 / max as f32;
+    Color::Rgb {
+        r: 100 + (155f32 * heat) as u8,
+        g: 0,
+        b: 0,
+    }
+}
+
+/// Get the {count} keys with the most errors, most first
+/// Keys with the same number of errors are sorted so the order is stable
+pub fn top_errors(errors: &HashMap<char, u32>, count: usize) -> Vec<(char, u32)> {
+    let mut top: Vec<(char, u32)> = errors
+        .iter()
+        .filter(|(_, errors)| **errors > 0)
+        .map(|(key, errors)| (*key, *errors))
+        .collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    top.truncate(count);
+    top
+}
+
+/// Get the name of a key to show in the summary
+pub fn key_name(key: char) -> String {
+    match key {
+        ' ' => "space".into(),
+        key => key.to_string(),
+    }
+}
+
+/// Render the keyboard with each key colored by the number of times it was mistyped
+pub fn render(errors: &HashMap<char, u32>) -> String {
+    let count = |key: char| {
+        errors
+            .iter()
+            .filter(|(ch, _)| ch.to_ascii_lowercase() == key)
+            .map(|(_, count)| count)
+            .sum::<u32>()
+    };
+    let max = ROWS
+        .iter()
+        .flat_map(|row| row.chars())
+        .chain([' '])
+        .map(count)
+        .max()
+        .unwrap_or(0);
+    let mut keymap = String::new();
+    for (indent, row) in ROWS.iter().enumerate() {
+        keymap += &" ".repeat(indent * 2);
+        for key in row.chars() {
+            let color = key_color(count(key), max);
+            keymap += &format!(" {} ", key).on(color).to_string();
+            keymap += " ";
+        }
+        keymap += "\n";
+    }
+    let color = key_color(count(' '), max);
+    keymap += &" ".repeat(ROWS.len() * 2 + 8);
+    keymap += &format!("{:^20}", "space").on(color).to_string();
+    keymap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_color_test() {
+        assert_eq!(key_color(0, 0), UNTOUCHED);
+        assert_eq!(key_color(0, 5), UNTOUCHED);
+        assert_eq!(key_color(5, 5), Color::Rgb { r: 255, g: 0, b: 0 });
+        assert_eq!(key_color(1, 5), Color::Rgb { r: 131, g: 0, b: 0 });
+    }
+
+    #[test]
+    fn top_errors_test() {
+        let errors = HashMap::from([('a', 3), ('b', 1), (' ', 3), ('c', 2), ('d', 0)]);
+        assert_eq!(top_errors(&errors, 3), [(' ', 3), ('a', 3), ('c', 2)]);
+        assert_eq!(top_errors(&errors, 10).len(), 4);
+        assert!(top_errors(&HashMap::new(), 3).is_empty());
+        assert_eq!(key_name(' '), "space");
+        assert_eq!(key_name('a'), "a");
+    }
+
+    #[test]
+    fn render_test() {
+        let errors = HashMap::from([('a', 3), ('A', 1), (' ', 2)]);
+        let keymap = render(&errors);
+        for key in ROWS.iter().flat_map(|row| row.chars()) {
+            assert!(keymap.contains(&format!(" {} ", key)));
+        }
+        assert!(keymap.contains("space"));
+        assert_eq!(keymap.lines().count(), ROWS.len() + 1);
+    }
+}
@@ -0,0 +1,83 @@
+//! Keeps track of how well each word has been learned across sessions
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// The name of the file the learning progress is stored in
+const FILE_NAME: &str = "learning.json";
+/// The number of times in a row a word must be typed well to graduate
+const GRADUATE_STREAK: u32 = 5;
+/// The most seconds per character a word can take to count as typed quickly
+const SECONDS_PER_CHAR: f32 = 0.3;
+
+/// The number of times in a row each word has been typed correctly and quickly
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Learning {
+    streaks: HashMap<String, u32>,
+}
+
+impl Learning {
+    /// Load the learning progress from previous sessions
+    /// Returns a message describing the problem if the saved file is invalid
+    pub fn load() -> Result<Self, String> {
+        crate::storage::load(FILE_NAME)
+    }
+
+    /// Save the learning progress for future sessions
+    pub fn save(&self) -> std::io::Result<()> {
+        crate::storage::save(FILE_NAME, self)
+    }
+
+    /// Record an attempt at typing a word
+    /// Returns true if the word is graduated after the attempt
+    pub fn record(&mut self, word: &str, correct: bool, duration: Duration) -> bool {
+        let quick = duration.as_secs_f32() <= word.chars().count() as f32 * SECONDS_PER_CHAR;
+        let streak = self.streaks.entry(word.to_string()).or_default();
+        if correct && quick {
+            *streak += 1;
+        } else {
+            *streak = 0;
+        }
+        *streak >= GRADUATE_STREAK
+    }
+
+    /// Get all of the words that have been graduated
+    pub fn graduated(&self) -> HashSet<String> {
+        self.streaks
+            .iter()
+            .filter(|(_, &streak)| streak >= GRADUATE_STREAK)
+            .map(|(word, _)| word.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUICK: Duration = Duration::from_millis(100);
+    const SLOW: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn learning_record_test() {
+        let mut learning = Learning::default();
+        for _ in 1..GRADUATE_STREAK {
+            assert!(!learning.record("word", true, QUICK));
+        }
+        assert!(learning.record("word", true, QUICK));
+        assert!(learning.record("word", true, QUICK));
+        assert!(!learning.record("word", false, QUICK));
+        assert!(!learning.record("other", true, SLOW));
+    }
+
+    #[test]
+    fn learning_graduated_test() {
+        let mut learning = Learning::default();
+        for _ in 0..GRADUATE_STREAK {
+            learning.record("learned", true, QUICK);
+            learning.record("slow", true, SLOW);
+            learning.record("wrong", false, QUICK);
+        }
+        assert_eq!(learning.graduated(), HashSet::from(["learned".to_string()]));
+    }
+}
@@ -0,0 +1,3797 @@
+//! Core logic of a typing test application
+//! Tests can be driven with actions and scored without drawing anything
+mod action;
+mod caret;
+mod config;
+mod coverage;
+mod export;
+mod history;
+mod input;
+mod keymap;
+mod learning;
+mod line;
+mod menu;
+mod quote;
+mod record;
+mod session;
+mod stats;
+mod stop;
+mod stopwatch;
+mod storage;
+
+pub use action::Action;
+pub use input::{InputSource, ScriptedInput, TerminalInput};
+pub use line::Line;
+pub use stats::{Summary, TestResult};
+
+use caret::{CaretStyle, SetCaret};
+use config::Config;
+use coverage::Coverage;
+use crossterm::{
+    cursor, execute, queue,
+    style::{Color, Print, Stylize},
+    terminal,
+};
+use history::{is_new_best, History, Stats, LEADERBOARD_LEN};
+use learning::Learning;
+use line::{center_padding, parse_color, Difficulty, Generator, Theme, Weighting, WpmTiers};
+use menu::Menu;
+use quote::{FetchStatus, Prefetch, QuoteSource};
+use rand::{rngs::StdRng, SeedableRng};
+use record::{Header, Keystroke, Recorder, ReplayInput};
+use session::Session;
+use stats::{
+    accuracy, aggregate, average, char_wpm, consistency, format_metric, improvement, pace_position,
+    slowest_words, sparkline, total, wpm, Recent, MAX_PRECISION, MIN_ELAPSED,
+};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use stop::{Progress, StopCondition};
+use stopwatch::Stopwatch;
+use structopt::StructOpt;
+
+/// Used by structopt for parsing command-line arguments
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "typing_test",
+    about = r#"A program to test your typing speed
+  Controls:
+    Esc - Exit test
+    Tab - Restart test
+    Letters - Enter input into the test
+    Backspace - Undo input from the test
+    F2 - Pause or resume test
+    F3 - Skip the current line
+    Ctrl+Backspace - Undo the current word
+    Ctrl+C - Exit right away without a summary or saving the test
+    Enter - Exit the summary screen
+"#
+)]
+pub struct Args {
+    /// The number of words to type before a test ends
+    #[structopt(short, long, name = "WORDS")]
+    number: Option<u32>,
+
+    /// How long the test should run in seconds
+    #[structopt(short, long, name = "SECONDS")]
+    time: Option<u64>,
+
+    /// Whether or not the test should run in Quote Mode
+    #[structopt(short, long)]
+    quote: bool,
+
+    /// A custom quote to use
+    #[structopt(short, long, name = "QUOTE")]
+    custom_quote: Option<String>,
+
+    /// Whether or not to use bundled quotes instead of fetching them
+    #[structopt(long)]
+    offline: bool,
+
+    /// The url of the API to fetch quotes from
+    #[structopt(long, value_name = "URL")]
+    quote_url: Option<String>,
+
+    /// The number of seconds to wait for a quote before trying again
+    #[structopt(long, value_name = "SECONDS", default_value = "5")]
+    quote_timeout: u64,
+
+    /// A file containing a custom quote to use
+    #[structopt(long, name = "FILE", parse(from_os_str))]
+    quote_file: Option<PathBuf>,
+
+    /// The number of decimal places to display metrics with (0-4)
+    #[structopt(long, name = "PLACES", default_value = "2")]
+    precision: usize,
+
+    /// The language of the generated words
+    #[structopt(long, default_value = "english")]
+    language: String,
+
+    /// Generate words from a file of whitespace separated words instead of a language
+    #[structopt(long, parse(from_os_str), value_name = "FILE")]
+    word_file: Option<PathBuf>,
+
+    /// Generate words from a file with a word and a weight separated by a tab on each line
+    /// Words with higher weights are picked more often
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "FILE",
+        conflicts_with = "word_file"
+    )]
+    words_file_weighted: Option<PathBuf>,
+
+    /// Print the 10 best results and exit
+    #[structopt(long)]
+    leaderboard: bool,
+
+    /// Only show results of this mode on the leaderboard, like "quote" or "30 words"
+    #[structopt(long, requires = "leaderboard")]
+    mode: Option<String>,
+
+    /// Print the first lines of the test without running it and exit
+    #[structopt(long)]
+    preview: bool,
+
+    /// Print the languages words can be generated in and exit
+    #[structopt(long)]
+    list_languages: bool,
+
+    /// Only use words of certain lengths: easy, medium, or hard
+    #[structopt(long, possible_values = &["easy", "medium", "hard"])]
+    difficulty: Option<Difficulty>,
+
+    /// The fewest characters a generated word can have
+    #[structopt(long, value_name = "LENGTH")]
+    min_word_length: Option<usize>,
+
+    /// The most characters a generated word can have
+    #[structopt(long, value_name = "LENGTH")]
+    max_word_length: Option<usize>,
+
+    /// Print the final result as JSON instead of text
+    #[structopt(long)]
+    json: bool,
+
+    /// Append the result of each test to a csv file
+    #[structopt(long, parse(from_os_str), value_name = "FILE")]
+    csv: Option<PathBuf>,
+
+    /// Log each keystroke and when it was pressed to a file as JSON lines
+    /// The seed or quote is saved on the first line so the test can be replayed
+    #[structopt(long, parse(from_os_str), value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Play back keystrokes logged with --record instead of reading the keyboard
+    /// Pass the same mode flags as when recording; the seed or quote comes from the file
+    #[structopt(long, parse(from_os_str), value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// How many times faster than recorded to play back keystrokes
+    #[structopt(long, value_name = "MULTIPLIER", default_value = "1")]
+    replay_speed: f64,
+
+    /// Generate the same words every time the same seed is given
+    #[structopt(long)]
+    seed: Option<u64>,
+
+    /// Whether or not to pick common words more often than rare words
+    #[structopt(long)]
+    weighted: bool,
+
+    /// Whether or not to add punctuation and capitalization to generated words
+    #[structopt(long)]
+    punctuation: bool,
+
+    /// Whether or not to replace some generated words with numbers
+    #[structopt(long)]
+    numbers: bool,
+
+    /// Practice typing lines of code instead of words
+    #[structopt(long)]
+    code: bool,
+
+    /// The fraction of generated words to capitalize, from 0 to 1
+    #[structopt(long, value_name = "RATIO")]
+    capitalize_ratio: Option<f64>,
+
+    /// The most characters a line can have, defaulting to the terminal width for generated lines
+    /// Quotes are split into lines of 10 words unless a width is given
+    #[structopt(long, name = "COLUMNS", alias = "max-line-width")]
+    width: Option<usize>,
+
+    /// Whether or not to track learned words and show them less often
+    #[structopt(long)]
+    learn: bool,
+
+    /// Whether or not to show a keyboard colored by mistyped keys at the end
+    #[structopt(long)]
+    keymap: bool,
+
+    /// Print the text of the test at the end with mistyped words in red
+    #[structopt(long)]
+    transcript: bool,
+
+    /// The number of times in a row to type the custom quote
+    #[structopt(long, name = "ATTEMPTS")]
+    repeat_quote: Option<u32>,
+
+    /// The number of times in a row to run the test
+    #[structopt(long, value_name = "N")]
+    repeat: Option<u32>,
+
+    /// Whether or not to keep track of which words of the word list have been seen
+    #[structopt(long)]
+    track_coverage: bool,
+
+    /// Print the percentage of the word list that has been seen and exit
+    #[structopt(long)]
+    coverage: bool,
+
+    /// Print statistics about every saved result and exit
+    #[structopt(long)]
+    stats: bool,
+
+    /// Whether or not to ignore spaces typed at the start of a line
+    #[structopt(long)]
+    trim_leading_space: bool,
+
+    /// How many minutes to practice for while cycling through drills
+    #[structopt(long, name = "MINUTES")]
+    session: Option<u64>,
+
+    /// A built-in color theme: dark, light, solarized, or monokai
+    #[structopt(long = "theme", value_name = "NAME", parse(try_from_str = Theme::named))]
+    named_theme: Option<Theme>,
+
+    /// The color of incorrectly typed characters as a hex color or name
+    #[structopt(long, value_name = "COLOR", parse(try_from_str = parse_color))]
+    error_color: Option<Color>,
+
+    /// The color of correctly typed characters as a hex color or name
+    #[structopt(long, value_name = "COLOR", parse(try_from_str = parse_color))]
+    correct_color: Option<Color>,
+
+    /// The color of characters that have not been typed yet as a hex color or name
+    #[structopt(long, value_name = "COLOR", parse(try_from_str = parse_color))]
+    pending_color: Option<Color>,
+
+    /// The shape of the caret: block, underline, or bar
+    #[structopt(long, possible_values = &["block", "underline", "bar"])]
+    caret: Option<CaretStyle>,
+
+    /// Draw this character at the typing position instead of using the terminal's cursor
+    #[structopt(long, value_name = "CHAR")]
+    caret_char: Option<char>,
+
+    /// Draw incorrect characters like correct ones instead of in the error color
+    #[structopt(long)]
+    no_error_highlight: bool,
+
+    /// The number of upcoming lines shown below the current one
+    #[structopt(long, value_name = "LINES", default_value = "1")]
+    lookahead: usize,
+
+    /// Hide the live score while typing and only show the summary
+    #[structopt(long)]
+    quiet: bool,
+
+    /// Center the lines of the test horizontally
+    #[structopt(long)]
+    center: bool,
+
+    /// Hide the upcoming lines so only the current line is shown
+    #[structopt(long)]
+    no_next_line: bool,
+
+    /// Hide mistakes and accuracy while typing and only reveal them in the summary
+    #[structopt(long)]
+    blind: bool,
+
+    /// End the test as soon as a character is typed incorrectly
+    #[structopt(long)]
+    hardcore: bool,
+
+    /// Only allow moving past a word once it has been typed correctly
+    #[structopt(long)]
+    strict: bool,
+
+    /// Wait for the mistakes on the current line to be fixed before a word limit ends the test
+    #[structopt(long)]
+    must_be_clean: bool,
+
+    /// Ring the terminal bell when a character is typed incorrectly
+    #[structopt(long)]
+    beep: bool,
+
+    /// Mark where the cursor would be when typing at this speed
+    /// Exits with code 3 if the finished test is slower
+    #[structopt(long, value_name = "WPM")]
+    target_wpm: Option<u32>,
+
+    /// The number of seconds to count down before the test begins
+    #[structopt(long, value_name = "SECONDS")]
+    countdown: Option<u64>,
+
+    /// Ignore typing for this many milliseconds after the test is shown
+    #[structopt(long, value_name = "MS")]
+    delay_start: Option<u64>,
+
+    /// Only restart a started test when Tab is pressed twice in a row
+    #[structopt(long)]
+    confirm_restart: bool,
+
+    /// Type without a word or time limit until Esc is pressed
+    #[structopt(long)]
+    zen: bool,
+
+    /// Choose the test mode from a menu before starting
+    #[structopt(long)]
+    menu: bool,
+
+    /// The words read from the word file
+    #[structopt(skip)]
+    custom_words: Option<Vec<String>>,
+
+    /// How often each word is picked, read from the weighted word file
+    #[structopt(skip)]
+    word_weights: HashMap<String, u32>,
+
+    /// Type quotes one after another until the word or time limit is reached
+    #[structopt(long)]
+    endless_quotes: bool,
+
+    /// End the lines of quotes at the end of a sentence when one is near
+    #[structopt(long)]
+    sentence_lines: bool,
+
+    /// Do not repeat a word within a generated line
+    #[structopt(long)]
+    no_repeats: bool,
+
+    /// Restart with the same quote instead of getting a new one
+    #[structopt(long)]
+    same_quote: bool,
+
+    /// The keystrokes read from the replay file
+    #[structopt(skip)]
+    replay_keys: Vec<Keystroke>,
+
+    /// The colors lines are drawn with, set by the config file and color flags
+    #[structopt(skip)]
+    theme: Theme,
+
+    /// The wpm the current wpm changes color at, set by the config file
+    #[structopt(skip)]
+    wpm_tiers: WpmTiers,
+}
+
+/// Enum that indicates when to stop the typing test
+#[derive(Debug)]
+pub enum TestMode {
+    /// Stop the test after a certain number of correct words typed
+    WordCount(u32),
+    /// Stop the test after a certain number of seconds elapsed
+    TimeLimit(u64),
+    /// Stop the test after a number of correct words or seconds, whichever comes first
+    WordsOrTime { words: u32, seconds: u64 },
+    /// Stop the test after finishing the quote
+    QuoteMode {
+        remaining: String,
+        custom: Option<String>,
+    },
+    /// Never stop the test until the user quits
+    Zen,
+}
+
+impl std::fmt::Display for TestMode {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            TestMode::WordCount(wc) => write!(formatter, "{} words", wc),
+            TestMode::TimeLimit(seconds) => write!(formatter, "{} seconds", seconds),
+            TestMode::WordsOrTime { words, seconds } => {
+                write!(formatter, "{} words or {} seconds", words, seconds)
+            }
+            TestMode::QuoteMode { .. } => write!(formatter, "quote"),
+            TestMode::Zen => write!(formatter, "zen"),
+        }
+    }
+}
+
+/// The ways the program can finish, each with its own exit code
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The test was completed or quit by the user
+    Success,
+    /// Something went wrong while running the test
+    RuntimeError(String),
+    /// The command-line arguments could not be used
+    InvalidArgs(String),
+    /// The test was completed slower than the target wpm
+    TargetMissed,
+}
+
+impl Outcome {
+    /// Get the exit code the program should finish with
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Outcome::Success => 0,
+            Outcome::RuntimeError(_) => 1,
+            Outcome::InvalidArgs(_) => 2,
+            Outcome::TargetMissed => 3,
+        }
+    }
+}
+
+/// What to do once the summary screen is closed
+#[derive(Debug, PartialEq)]
+enum EndChoice {
+    /// Leave the summary screen and exit
+    Exit,
+    /// Start another test
+    Restart,
+}
+
+impl EndChoice {
+    /// Get the choice an action makes on the summary screen if it makes one
+    fn from_action(action: Action) -> Option<Self> {
+        match action {
+            Action::Enter | Action::Quit | Action::Interrupt => Some(EndChoice::Exit),
+            Action::Reset => Some(EndChoice::Restart),
+            _ => None,
+        }
+    }
+}
+
+/// holds info about current typing test
+pub struct TypingTest {
+    running: bool,
+    show_final_score: bool,
+    interrupted: bool,
+    stdout: Box<dyn Write>,
+    screen: Option<(u16, u16)>,
+    input: Box<dyn InputSource>,
+    recorder: Option<Recorder<std::fs::File>>,
+    replaying: bool,
+    previous_line: Line,
+    typed_lines: Vec<Line>,
+    line: Line,
+    upcoming: VecDeque<Line>,
+    lookahead: usize,
+    show_upcoming: bool,
+    center: bool,
+    quiet: bool,
+    test_mode: TestMode,
+    _word_count: u32,
+    _raw_word_count: u32,
+    _keystrokes: u32,
+    _errors: u32,
+    _fixed: u32,
+    _chars: usize,
+    _char_count: u32,
+    stopwatch: Option<Stopwatch>,
+    attempts: u32,
+    results: Vec<TestResult>,
+    earlier_results: Vec<TestResult>,
+    precision: usize,
+    generator: Generator,
+    learning: Option<Learning>,
+    word_instant: Option<Instant>,
+    errors: HashMap<char, u32>,
+    show_keymap: bool,
+    show_transcript: bool,
+    session: Option<Session>,
+    coverage: Option<Coverage>,
+    trim_leading_space: bool,
+    stop_conditions: Vec<Box<dyn StopCondition>>,
+    stop_reason: Option<&'static str>,
+    quotes: QuoteSource,
+    recent: Recent,
+    theme: Theme,
+    wpm_tiers: WpmTiers,
+    countdown: u64,
+    delay_start: Duration,
+    confirm_restart: bool,
+    last_tab: Option<Instant>,
+    json: bool,
+    csv: Option<PathBuf>,
+    caret: Option<CaretStyle>,
+    caret_char: Option<char>,
+    highlight_errors: bool,
+    blind: bool,
+    hardcore: bool,
+    must_be_clean: bool,
+    fit_width: bool,
+    strict: bool,
+    beep: bool,
+    target_wpm: Option<u32>,
+    ring_bell: bool,
+    last_beep: Option<Instant>,
+    quote: String,
+    same_quote: bool,
+    sentence_lines: bool,
+    max_line_width: Option<usize>,
+    word_times: Vec<(String, Duration)>,
+    wpm_samples: Vec<f32>,
+    /// The word times, wpm samples and missed keys that came from earlier attempts
+    kept_details: (usize, usize, HashMap<char, u32>),
+    /// The word times, missed keys and learning progress from before the current line
+    line_details: (usize, HashMap<char, u32>, Option<Learning>),
+    previous_line_details: (usize, HashMap<char, u32>, Option<Learning>),
+    last_sample: (f32, u32),
+    skipped_lines: u32,
+    shown: Option<Instant>,
+    reaction: Option<Duration>,
+    personal_best: Option<f32>,
+    endless_quotes: Option<String>,
+    next_quote: Option<Prefetch>,
+    fetch_status: Option<&'static str>,
+}
+
+impl TypingTest {
+    /// Create a test from parsed arguments without touching the terminal
+    pub fn new(args: Args) -> Result<Self, String> {
+        let words = word_list(&args)?;
+        let session = args.session.map(Session::new);
+        let quotes = QuoteSource {
+            url: args
+                .quote_url
+                .clone()
+                .unwrap_or_else(|| quote::QUOTE_URL.into()),
+            timeout: Duration::from_secs(args.quote_timeout),
+            offline: args.offline,
+        };
+        let test_mode = if let Some(session) = &session {
+            match session.drill() {
+                TestMode::QuoteMode { custom, .. } => TestMode::QuoteMode {
+                    remaining: quotes.quote_or_fallback(report_to(&mut io::stderr())),
+                    custom,
+                },
+                mode => mode,
+            }
+        } else if args.zen {
+            TestMode::Zen
+        } else if let (Some(words), Some(seconds)) = (args.number, args.time) {
+            TestMode::WordsOrTime { words, seconds }
+        } else if let Some(seconds) = args.time {
+            TestMode::TimeLimit(seconds)
+        } else if args.quote {
+            TestMode::QuoteMode {
+                custom: args.custom_quote.clone(),
+                remaining: match args.custom_quote {
+                    Some(quote) => quote,
+                    None => quotes.quote_or_fallback(report_to(&mut io::stderr())),
+                },
+            }
+        } else {
+            TestMode::WordCount(args.number.unwrap_or(30))
+        };
+        // a broken file is reported instead of being replaced by an empty one when the test ends
+        let learning = args.learn.then(Learning::load).transpose()?;
+        let coverage = args.track_coverage.then(Coverage::load).transpose()?;
+        let generator = Generator {
+            words,
+            // the weights of a weighted word file are used instead of how common words are
+            weighting: if !args.word_weights.is_empty() {
+                Weighting::ByWord(args.word_weights)
+            } else if args.weighted {
+                Weighting::ByRank
+            } else {
+                Weighting::Even
+            },
+            distribution: None,
+            learned: learning
+                .as_ref()
+                .map(Learning::graduated)
+                .unwrap_or_default(),
+            punctuation: args.punctuation,
+            numbers: args.numbers,
+            code: args.code,
+            no_repeats: args.no_repeats,
+            capitalize_ratio: args
+                .capitalize_ratio
+                .map_or(0.0, |ratio| ratio.clamp(0.0, 1.0)),
+            width: Some(match args.width {
+                Some(width) => width,
+                None => fit_width(terminal::size().map_or(80, |(columns, _)| columns)),
+            }),
+            mid_sentence: false,
+            rng: args
+                .seed
+                .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+        };
+        let quote = match &test_mode {
+            TestMode::QuoteMode { remaining, .. } => remaining.clone(),
+            _ => String::new(),
+        };
+        let mut test = Self {
+            running: true,
+            show_final_score: true,
+            stdout: Box::new(io::stdout()),
+            screen: None,
+            input: match &args.replay {
+                Some(_) => Box::new(ReplayInput::new(&args.replay_keys, args.replay_speed)),
+                None => Box::new(TerminalInput),
+            },
+            replaying: args.replay.is_some(),
+            recorder: match &args.record {
+                Some(path) => {
+                    let header = Header {
+                        seed: args.seed,
+                        quote: Some(quote.clone()).filter(|quote| !quote.is_empty()),
+                    };
+                    Some(Recorder::create(path, &header)?)
+                }
+                None => None,
+            },
+            previous_line: Line::EMPTY,
+            typed_lines: Vec::new(),
+            line: Line::EMPTY,
+            upcoming: VecDeque::new(),
+            lookahead: args.lookahead,
+            show_upcoming: !args.no_next_line,
+            center: args.center,
+            quiet: args.quiet,
+            test_mode,
+            _word_count: 0,
+            _raw_word_count: 0,
+            _keystrokes: 0,
+            _errors: 0,
+            _fixed: 0,
+            _chars: 0,
+            _char_count: 0,
+            stopwatch: None,
+            attempts: args.repeat_quote.or(args.repeat).unwrap_or(1),
+            results: Vec::new(),
+            earlier_results: Vec::new(),
+            precision: args.precision,
+            generator,
+            learning,
+            word_instant: None,
+            errors: HashMap::new(),
+            show_keymap: args.keymap,
+            show_transcript: args.transcript,
+            session,
+            coverage,
+            trim_leading_space: args.trim_leading_space,
+            stop_conditions: Vec::new(),
+            stop_reason: None,
+            quotes,
+            recent: Recent::default(),
+            theme: args.theme,
+            wpm_tiers: args.wpm_tiers,
+            countdown: args.countdown.unwrap_or(0),
+            delay_start: Duration::from_millis(args.delay_start.unwrap_or(0)),
+            confirm_restart: args.confirm_restart,
+            last_tab: None,
+            json: args.json,
+            csv: args.csv,
+            caret: args.caret,
+            caret_char: args.caret_char,
+            quote,
+            same_quote: args.same_quote,
+            sentence_lines: args.sentence_lines,
+            max_line_width: args.width,
+            highlight_errors: !args.no_error_highlight && !args.blind,
+            blind: args.blind,
+            hardcore: args.hardcore,
+            interrupted: false,
+            must_be_clean: args.must_be_clean,
+            fit_width: args.width.is_none(),
+            strict: args.strict,
+            beep: args.beep,
+            target_wpm: args.target_wpm,
+            ring_bell: false,
+            last_beep: None,
+            word_times: Vec::new(),
+            wpm_samples: Vec::new(),
+            kept_details: (0, 0, HashMap::new()),
+            line_details: Default::default(),
+            previous_line_details: Default::default(),
+            last_sample: (0f32, 0),
+            skipped_lines: 0,
+            shown: None,
+            reaction: None,
+            personal_best: None,
+            endless_quotes: args.endless_quotes.then(String::new),
+            next_quote: None,
+            fetch_status: None,
+        };
+        test.line = test.new_line();
+        test.fill_upcoming();
+        test.update_stop_conditions();
+        test.see_line();
+        test.start_line_details();
+        Ok(test)
+    }
+
+    /// Set the conditions that end the test based on the test mode
+    fn update_stop_conditions(&mut self) {
+        self.stop_conditions = stop::for_mode(&self.test_mode, self.must_be_clean);
+        if self.session.is_some() {
+            self.stop_conditions.push(Box::new(stop::SessionExpired));
+        }
+        if self.hardcore {
+            self.stop_conditions.push(Box::new(stop::FirstMistake));
+        }
+    }
+
+    /// Get the state of the test that stop conditions are checked against
+    fn progress(&self) -> Progress {
+        Progress {
+            words: self.word_count(),
+            elapsed: self.stopwatch.map(|stopwatch| stopwatch.elapsed()),
+            text_done: self.line.done() && self.upcoming.iter().all(Line::done),
+            session_expired: self.session.as_ref().is_some_and(Session::expired),
+            errors: self.keystrokes().1,
+            line_clean: self.line.is_clean(),
+        }
+    }
+
+    /// Mark the words of the current line as seen if coverage is being tracked
+    fn see_line(&mut self) {
+        if matches!(self.test_mode, TestMode::QuoteMode { .. })
+            || self.endless_quotes.is_some()
+            || self.generator.code
+        {
+            return;
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record(self.line.expected());
+        }
+    }
+
+    /// Read key presses from {input} instead of the terminal
+    pub fn set_input(&mut self, input: impl InputSource + 'static) {
+        self.input = Box::new(input);
+    }
+
+    /// Draw to {output} as if it were a terminal of {size} columns and rows
+    pub fn set_screen(&mut self, output: impl Write + 'static, size: (u16, u16)) {
+        self.stdout = Box::new(output);
+        self.screen = Some(size);
+    }
+
+    /// Get the number of columns and rows of the screen being drawn to
+    fn screen_size(&self) -> crossterm::Result<(u16, u16)> {
+        self.screen.map_or_else(terminal::size, Ok)
+    }
+
+    /// Get the results of each finished test
+    pub fn results(&self) -> &[TestResult] {
+        &self.results
+    }
+
+    /// Get the mode the test is being run in
+    pub fn test_mode(&self) -> &TestMode {
+        &self.test_mode
+    }
+
+    /// Get the line being typed
+    pub fn line(&self) -> &Line {
+        &self.line
+    }
+
+    /// calculate word count
+    pub fn word_count(&self) -> u32 {
+        self._word_count + self.line.word_count()
+    }
+
+    /// calculate the number of correctly typed characters including spaces
+    pub fn char_count(&self) -> u32 {
+        self._char_count + self.line.char_count()
+    }
+
+    /// calculate word count including incorrect words
+    pub fn raw_word_count(&self) -> u32 {
+        self._raw_word_count + self.line.raw_word_count()
+    }
+
+    /// calculate the number of characters typed and how many were incorrect
+    pub fn keystrokes(&self) -> (u32, u32) {
+        let (keystrokes, errors) = self.line.keystrokes();
+        (self._keystrokes + keystrokes, self._errors + errors)
+    }
+
+    /// calculate the number of incorrect characters that were removed with backspace
+    pub fn fixed_keystrokes(&self) -> u32 {
+        self._fixed + self.line.fixed()
+    }
+
+    /// calculate the percentage of correctly typed characters
+    pub fn accuracy(&self) -> Option<f32> {
+        let (keystrokes, errors) = self.keystrokes();
+        accuracy(keystrokes, errors)
+    }
+
+    /// Get the result of the test so far
+    /// Returns None if nothing has been typed yet
+    pub fn result(&self) -> Option<TestResult> {
+        let stopwatch = self.stopwatch?;
+        let (keystrokes, errors) = self.keystrokes();
+        Some(TestResult {
+            mode: self.test_mode.to_string(),
+            words: self.word_count(),
+            raw_words: self.raw_word_count(),
+            elapsed: stopwatch.elapsed().as_secs_f32(),
+            keystrokes,
+            errors,
+        })
+    }
+
+    /// Summarize the test so far, including its wpm and accuracy
+    /// Returns None if nothing has been typed yet
+    pub fn summary(&self) -> Option<Summary> {
+        self.result().as_ref().map(Summary::from)
+    }
+
+    /// Check whether the test should stop, remembering the reason if it should
+    pub fn check_stop(&mut self) -> Option<&'static str> {
+        self.stop_reason = stop::check(&self.stop_conditions, &self.progress());
+        self.stop_reason
+    }
+
+    /// Get how much of the test is left along with a label for it
+    /// Quotes show the percentage of the quote that has been typed
+    /// Returns None if the test has no end
+    fn remaining(&self) -> Option<(&'static str, String)> {
+        Some(match &self.test_mode {
+            TestMode::WordCount(words) => ("Remaining", self.words_left(*words)),
+            TestMode::TimeLimit(seconds) => ("Remaining", self.time_left(*seconds)),
+            TestMode::WordsOrTime { words, seconds } => (
+                "Remaining",
+                format!(
+                    "{} or {}",
+                    self.words_left(*words),
+                    self.time_left(*seconds)
+                ),
+            ),
+            TestMode::QuoteMode { remaining, .. } => {
+                let (done, total) = self.quote_progress(remaining);
+                let percent = (done * 100).checked_div(total).unwrap_or(100);
+                ("Complete", format!("{}%", percent))
+            }
+            TestMode::Zen => return None,
+        })
+    }
+
+    /// Get the number of characters of the quote that have been typed and its total length
+    fn quote_progress(&self, remaining: &str) -> (usize, usize) {
+        let done = self._chars + self.line.typed_len();
+        let left = std::iter::once(&self.line)
+            .chain(&self.upcoming)
+            .map(Line::expected)
+            .chain([remaining])
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .count();
+        (done, self._chars + left)
+    }
+
+    /// Get the fraction of the test that has been completed
+    /// Tests that end on words or time are as complete as whichever is closer to the end
+    /// Returns None if the test has no end
+    fn completed(&self) -> Option<f32> {
+        let ratio = |done: f32, total: f32| if total > 0f32 { done / total } else { 1f32 };
+        let words = |words: u32| ratio(self.word_count() as f32, words as f32);
+        let time = |seconds: u64| {
+            let elapsed = self.stopwatch.map_or(0f32, |s| s.elapsed().as_secs_f32());
+            ratio(elapsed, seconds as f32)
+        };
+        let completed = match &self.test_mode {
+            TestMode::WordCount(n) => words(*n),
+            TestMode::TimeLimit(seconds) => time(*seconds),
+            TestMode::WordsOrTime { words: n, seconds } => words(*n).max(time(*seconds)),
+            TestMode::QuoteMode { remaining, .. } => {
+                let (done, total) = self.quote_progress(remaining);
+                ratio(done as f32, total as f32)
+            }
+            TestMode::Zen => return None,
+        };
+        Some(completed.clamp(0f32, 1f32))
+    }
+
+    /// Describe how many of {words} words are left to type
+    fn words_left(&self, words: u32) -> String {
+        format!("{} words", words.saturating_sub(self.word_count()))
+    }
+
+    /// Describe how much of {seconds} seconds is left
+    fn time_left(&self, seconds: u64) -> String {
+        let limit = Duration::from_secs(seconds);
+        let elapsed = self.stopwatch.map_or(Duration::ZERO, |s| s.elapsed());
+        let left = limit.saturating_sub(elapsed).as_secs_f32();
+        format!("{:.*}s", self.precision, left)
+    }
+
+    /// Draw line containing words completed, time passed, wpm, and test mode
+    fn draw_score(&mut self) -> crossterm::Result<()> {
+        let time = match self.stopwatch {
+            Some(x) => x.elapsed().as_secs_f32(),
+            None => 0f32,
+        };
+        let wc = self.word_count();
+        let precision = self.precision;
+        let net = wpm(wc, time);
+        let mut wpm = format_metric(net, 6, precision).stylize();
+        if let Some(net) = net {
+            wpm = wpm.with(self.wpm_tiers.color(net));
+        }
+        let recent = format_metric(self.recent.wpm(), 6, precision);
+        let accuracy = match self.accuracy().filter(|_| !self.blind) {
+            Some(accuracy) => format!("{:6.*}%", precision, accuracy),
+            None => format!("{:>7}", "--"),
+        };
+        let mode = &self.test_mode;
+        let theme = self.theme;
+        queue!(
+            self.stdout,
+            Print(format!(
+                "{}: {}  {}: {:6.*}s  {}: {}  {}: {}  {}: {}  {}: {}",
+                "Words".with(theme.label_color(Color::Red)).bold(),
+                wc,
+                "Time".with(theme.label_color(Color::Green)).bold(),
+                precision,
+                time,
+                "wpm".with(theme.label_color(Color::Blue)).bold(),
+                wpm,
+                "Recent".with(theme.label_color(Color::Blue)),
+                recent,
+                "Accuracy".with(theme.label_color(Color::Cyan)).bold(),
+                accuracy,
+                "Mode".with(theme.label_color(Color::Yellow)).bold(),
+                mode
+            ))
+        )?;
+        if let Some((label, remaining)) = self.remaining() {
+            queue!(
+                self.stdout,
+                Print(format!(
+                    "  {}: {}",
+                    label.with(theme.label_color(Color::Yellow)).bold(),
+                    remaining
+                ))
+            )?;
+        }
+        if let Some(pb) = self.personal_best {
+            let label = format!("PB: {:.0}", pb);
+            let label = if is_new_best(Some(pb), net) {
+                label.with(theme.label_color(Color::Green)).bold()
+            } else {
+                label.stylize()
+            };
+            queue!(self.stdout, Print(format!("  {}", label)))?;
+        }
+        if let Some(completed) = self.completed() {
+            queue!(
+                self.stdout,
+                Print(format!("  {}", progress_bar(completed, PROGRESS_WIDTH)))
+            )?;
+        }
+        if let Some(session) = &self.session {
+            let remaining = session.remaining().as_secs();
+            queue!(
+                self.stdout,
+                Print(format!(
+                    "  {}: {}:{:02}",
+                    "Session".with(theme.label_color(Color::Magenta)).bold(),
+                    remaining / 60,
+                    remaining % 60
+                ))
+            )?;
+        }
+        if self.stopwatch.is_some_and(|stopwatch| stopwatch.paused()) {
+            queue!(
+                self.stdout,
+                Print(format!(
+                    "  {}",
+                    "Paused".with(theme.label_color(Color::Magenta)).bold()
+                ))
+            )?;
+        }
+        if self.restart_pending(Instant::now()) {
+            queue!(
+                self.stdout,
+                Print(format!(
+                    "  {}",
+                    "Press Tab again to restart"
+                        .with(theme.label_color(Color::Yellow))
+                        .bold()
+                ))
+            )?;
+        }
+        if let Some(status) = self.fetch_status {
+            queue!(
+                self.stdout,
+                Print(format!(
+                    "  {}: {}",
+                    "Quote".with(theme.label_color(Color::Magenta)).bold(),
+                    status
+                ))
+            )?;
+        }
+        if self.attempts > 1 {
+            let attempt = self.results.len() + 1;
+            let attempts = self.attempts;
+            queue!(
+                self.stdout,
+                Print(format!(
+                    "  {}: {}/{}",
+                    "Attempt".with(theme.label_color(Color::Magenta)).bold(),
+                    attempt,
+                    attempts
+                ))
+            )?;
+        }
+        queue!(self.stdout, cursor::MoveToNextLine(1))
+    }
+
+    /// Get each line of the test that was reached with correct words dimmed and mistyped words in red
+    fn transcript_lines(&self) -> Vec<String> {
+        let error = self.theme.error;
+        self.typed_lines
+            .iter()
+            .chain([&self.line])
+            .map(|line| {
+                line.word_marks()
+                    .into_iter()
+                    .map(|(word, correct)| {
+                        if correct {
+                            word.dim().to_string()
+                        } else {
+                            word.with(error).to_string()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    /// Get a table of how many keystrokes were correct, incorrect, and fixed
+    fn keystroke_lines(&self) -> Vec<String> {
+        let (keystrokes, errors) = self.keystrokes();
+        if keystrokes == 0 {
+            return Vec::new();
+        }
+        vec![
+            "Keystrokes: Total  Correct  Incorrect  Fixed".into(),
+            format!(
+                "            {:>5}  {:>7}  {:>9}  {:>5}",
+                keystrokes,
+                keystrokes - errors,
+                errors,
+                self.fixed_keystrokes()
+            ),
+        ]
+    }
+
+    /// Redraw the entire screen
+    fn render(&mut self) -> crossterm::Result<()> {
+        let width = self.screen_size()?.0 as usize;
+        self.clear()?;
+        if std::mem::take(&mut self.ring_bell) {
+            queue!(self.stdout, Print('\x07'))?;
+        }
+        if !self.quiet {
+            self.draw_score()?;
+        }
+        let highlight = self.highlight_errors;
+        let center = self.center;
+        let (pace, next_pace) = match self.stopwatch {
+            Some(stopwatch) => self.pace_columns(stopwatch.elapsed().as_secs_f32()),
+            None => (None, None),
+        };
+        let column = padding(&self.previous_line, width, center);
+        queue!(self.stdout, cursor::MoveToColumn(column))?;
+        self.previous_line
+            .draw(&mut self.stdout, width, &self.theme, highlight, None)?;
+        let column = padding(&self.line, width, center);
+        queue!(self.stdout, cursor::MoveToColumn(column))?;
+        self.line.draw_with_cursor(
+            &mut self.stdout,
+            width,
+            &self.theme,
+            highlight,
+            pace,
+            self.caret_char,
+        )?;
+        // the upcoming lines are still generated when hidden so they are ready to move up
+        for (i, line) in self.upcoming.iter().take(self.shown_upcoming()).enumerate() {
+            let pace = next_pace.filter(|_| i == 0);
+            queue!(
+                self.stdout,
+                cursor::MoveToColumn(padding(line, width, center))
+            )?;
+            line.draw(&mut self.stdout, width, &self.theme, highlight, pace)?;
+        }
+        let (x, y) = (self.cursor_column(width), self.line_row());
+        queue!(self.stdout, cursor::MoveTo(x, y))?;
+        self.stdout.flush()
+    }
+
+    /// Get the row the current line is drawn on
+    /// The previous line is above it and the score is above that unless it is hidden
+    fn line_row(&self) -> u16 {
+        if self.quiet {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Get the column the cursor is drawn at on the current line
+    fn cursor_column(&self, width: usize) -> u16 {
+        self.line.column(width) as u16 + padding(&self.line, width, self.center)
+    }
+
+    /// Get the column of the current or next line the pacer is on after {elapsed} seconds
+    /// The pacer is not shown without a target or once it falls behind the current line
+    fn pace_columns(&self, elapsed: f32) -> (Option<usize>, Option<usize>) {
+        let target = match self.target_wpm {
+            Some(target) => target,
+            None => return (None, None),
+        };
+        let column = match pace_position(target, elapsed).checked_sub(self._chars) {
+            Some(column) => column,
+            None => return (None, None),
+        };
+        // include the space typed to move to the next line
+        let line_len = self.line.expected().chars().count() + 1;
+        if column < line_len {
+            (Some(column), None)
+        } else {
+            (None, Some(column - line_len))
+        }
+    }
+
+    /// Move cursor to the next line and get next needed lines
+    fn get_next_line(&mut self) {
+        self._word_count += self.line.word_count();
+        self._raw_word_count += self.line.raw_word_count();
+        let (keystrokes, errors) = self.line.keystrokes();
+        self._keystrokes += keystrokes;
+        self._errors += errors;
+        self._fixed += self.line.fixed();
+        // include the space typed to move to the next line
+        self._chars += self.line.expected().chars().count() + 1;
+        self._char_count += self.line.char_count() + 1;
+        self.typed_lines.push(self.line.clone());
+        let next = self.next_upcoming();
+        self.previous_line = std::mem::replace(&mut self.line, next);
+        self.see_line();
+        self.start_line_details();
+    }
+
+    /// Get the number of upcoming lines that are drawn below the current one
+    fn shown_upcoming(&self) -> usize {
+        if self.show_upcoming {
+            self.upcoming.len()
+        } else {
+            0
+        }
+    }
+
+    /// Take the first upcoming line and generate another to keep the lookahead full
+    fn next_upcoming(&mut self) -> Line {
+        let next = match self.upcoming.pop_front() {
+            Some(line) => line,
+            None => self.new_line(),
+        };
+        self.fill_upcoming();
+        next
+    }
+
+    /// Generate lines until {lookahead} lines are waiting after the current one
+    fn fill_upcoming(&mut self) {
+        while self.upcoming.len() < self.lookahead {
+            let line = self.new_line();
+            self.upcoming.push_back(line);
+        }
+    }
+
+    /// Move on to the next line without counting anything typed on the current one
+    /// The skipped line cannot be returned to with backspace
+    fn skip_line(&mut self) {
+        self.skipped_lines += 1;
+        // the skipped line still counts toward quote progress and the pacer
+        self._chars += self.line.expected().chars().count() + 1;
+        self.discard_line_details();
+        self.line = self.next_upcoming();
+        self.see_line();
+        self.previous_line = Line::EMPTY;
+    }
+
+    /// Undo one typed character
+    /// At the start of a quote line this moves back to the end of the previous line instead
+    fn backspace(&mut self) {
+        let in_quote = matches!(self.test_mode, TestMode::QuoteMode { .. });
+        if in_quote && self.line.typed_len() == 0 && !self.previous_line.expected().is_empty() {
+            self.get_previous_line();
+        } else {
+            self.line.backspace();
+        }
+    }
+
+    /// Move the cursor back to the end of the previous line, undoing `get_next_line`
+    /// Only one line is kept so the line before the previous one cannot be returned to
+    fn get_previous_line(&mut self) {
+        let previous = std::mem::replace(&mut self.previous_line, Line::EMPTY);
+        self.line_details = self.previous_line_details.clone();
+        self.typed_lines.pop();
+        self._word_count -= previous.word_count();
+        self._raw_word_count -= previous.raw_word_count();
+        let (keystrokes, errors) = previous.keystrokes();
+        self._keystrokes -= keystrokes;
+        self._errors -= errors;
+        self._fixed -= previous.fixed();
+        self._chars -= previous.expected().chars().count() + 1;
+        self._char_count -= previous.char_count() + 1;
+        let line = std::mem::replace(&mut self.line, previous);
+        self.upcoming.push_front(line);
+        let last = self.upcoming.pop_back().unwrap_or(Line::EMPTY);
+        // put the words of the last upcoming line back so they are taken again
+        if let TestMode::QuoteMode { remaining, .. } = &mut self.test_mode {
+            *remaining = [last.expected(), remaining.as_str()]
+                .into_iter()
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+    }
+
+    /// Take the next line from the quote or generate one
+    /// Endless quotes get a new quote whenever the last one runs out
+    fn new_line(&mut self) -> Line {
+        if let TestMode::QuoteMode { remaining, .. } = &mut self.test_mode {
+            Line::from_quote(remaining, self.sentence_lines, self.max_line_width)
+        } else if let Some(mut remaining) = self.endless_quotes.take() {
+            refill(&mut remaining, || self.next_endless_quote());
+            let line = Line::from_quote(&mut remaining, self.sentence_lines, self.max_line_width);
+            self.endless_quotes = Some(remaining);
+            line
+        } else {
+            Line::new(&mut self.generator)
+        }
+    }
+
+    /// Get the next of the endless quotes and start fetching the one after it in the background
+    /// The stopwatch is paused while waiting on a quote that is still being fetched
+    fn next_endless_quote(&mut self) -> String {
+        let quote = match self.next_quote.take() {
+            Some(prefetch) => {
+                let wait = !prefetch.is_ready()
+                    && self.stopwatch.is_some_and(|stopwatch| !stopwatch.paused());
+                if wait {
+                    self.toggle_pause();
+                }
+                let quote = prefetch.quote_or_fallback(|status| self.set_fetch_status(status));
+                if wait {
+                    self.toggle_pause();
+                }
+                quote
+            }
+            // the first quote is fetched before the test is drawn
+            None => self.quotes.quote_or_fallback(report_to(&mut self.stdout)),
+        };
+        self.next_quote = Some(self.quotes.prefetch());
+        quote
+    }
+
+    /// Show how fetching a quote is going in the score line
+    /// The test is already drawn so the status is not printed where the cursor is
+    fn set_fetch_status(&mut self, status: FetchStatus) {
+        self.fetch_status = match status {
+            FetchStatus::Fetching => Some("fetching..."),
+            FetchStatus::Fetched => None,
+            FetchStatus::Failed(_) => Some("using a bundled quote"),
+        };
+        // nothing else draws the screen while waiting on the quote
+        let _ = self.render();
+    }
+
+    /// Get the text of the first {count} lines of the test
+    /// Quotes may have fewer lines
+    fn preview(&mut self, count: usize) -> Vec<String> {
+        let mut lines = std::iter::once(&self.line)
+            .chain(&self.upcoming)
+            .map(|line| line.expected().to_string())
+            .collect::<Vec<_>>();
+        while lines.len() < count {
+            lines.push(self.new_line().expected().to_string());
+        }
+        lines.retain(|line| !line.is_empty());
+        lines.truncate(count);
+        lines
+    }
+
+    /// clear the screen
+    fn clear(&mut self) -> crossterm::Result<()> {
+        queue!(
+            self.stdout,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0),
+        )
+    }
+
+    /// Wait a short time for input and turn it into an action
+    fn next_action(&mut self) -> crossterm::Result<Action> {
+        let event = self.input.poll()?;
+        if let (Some(recorder), Some(event)) = (&mut self.recorder, &event) {
+            recorder.record(event, Instant::now())?;
+        }
+        Ok(event.and_then(Action::from_event).unwrap_or(Action::Tick))
+    }
+
+    /// Apply an action to the test
+    /// Returns true if the screen needs to be redrawn
+    pub fn update(&mut self, action: Action) -> crossterm::Result<bool> {
+        match action {
+            Action::Quit => self.quit(),
+            Action::Interrupt => self.interrupt(),
+            Action::Reset => {
+                if self.confirm_reset(Instant::now()) {
+                    self.discard_details();
+                    self.reset()?;
+                }
+            }
+            Action::Backspace => self.backspace(),
+            Action::BackspaceWord => self.line.backspace_word(),
+            Action::Pause => self.toggle_pause(),
+            Action::Skip => self.skip_line(),
+            Action::Resize(columns, rows) => self.resize(columns, rows),
+            Action::Enter => return Ok(false),
+            Action::Type(ch) => return Ok(self.type_char(ch)),
+            Action::Tick => return Ok(self.stopwatch.is_some() || self.session.is_some()),
+        }
+        Ok(true)
+    }
+
+    /// Fit new lines to the resized screen
+    /// The whole screen is redrawn since everything may have moved
+    fn resize(&mut self, columns: u16, rows: u16) {
+        if let Some(size) = &mut self.screen {
+            *size = (columns, rows);
+        }
+        if self.fit_width {
+            self.generator.width = Some(fit_width(columns));
+        }
+    }
+
+    /// Pause the timer if it is running or resume it if it is paused
+    fn toggle_pause(&mut self) {
+        if let Some(stopwatch) = &mut self.stopwatch {
+            if stopwatch.paused() {
+                stopwatch.resume();
+            } else {
+                stopwatch.pause();
+            }
+        }
+    }
+
+    /// Whether Tab pressed at {now} should restart the test
+    /// With --confirm-restart a started test only restarts if Tab was also pressed just before
+    fn confirm_reset(&mut self, now: Instant) -> bool {
+        if !self.confirm_restart || self.stopwatch.is_none() {
+            return true;
+        }
+        let confirmed = self.restart_pending(now);
+        self.last_tab = if confirmed { None } else { Some(now) };
+        confirmed
+    }
+
+    /// Whether pressing Tab at {now} would confirm a restart
+    fn restart_pending(&self, now: Instant) -> bool {
+        self.last_tab
+            .is_some_and(|tab| now.saturating_duration_since(tab) < RESTART_WINDOW)
+    }
+
+    /// Whether typing is still being ignored at {now} because the test was just shown
+    fn in_grace_period(&self, now: Instant) -> bool {
+        self.shown
+            .is_some_and(|shown| now.saturating_duration_since(shown) < self.delay_start)
+    }
+
+    /// Handle a typed character
+    /// Returns false if the character was ignored
+    fn type_char(&mut self, ch: char) -> bool {
+        if self.stopwatch.is_none() && self.in_grace_period(Instant::now()) {
+            return false;
+        }
+        if ch == ' ' && self.trim_leading_space && self.line.index() == 0 {
+            return false;
+        }
+        if ch == ' ' && self.strict && !self.line.current_word_correct() {
+            return false;
+        }
+        match &mut self.stopwatch {
+            Some(stopwatch) => stopwatch.resume(),
+            None => {
+                let now = Instant::now();
+                self.stopwatch = Some(Stopwatch::start());
+                self.word_instant = Some(now);
+                self.reaction = self.shown.map(|shown| reaction_time(shown, now));
+            }
+        }
+        if ch == ' ' {
+            self.finish_word();
+        }
+        if ch == ' ' && self.line.done() {
+            self.get_next_line();
+            true
+        } else {
+            if let Some(expected) = self.line.next_expected() {
+                if expected != ch {
+                    *self.errors.entry(expected).or_default() += 1;
+                }
+            }
+            if self.beep && self.line.is_mistake(ch) {
+                self.beep();
+            }
+            self.line.add_char(ch)
+        }
+    }
+
+    /// Ring the bell on the next render unless it rang too recently
+    /// This keeps a held key from ringing the bell continuously
+    fn beep(&mut self) {
+        if self
+            .last_beep
+            .is_some_and(|last| last.elapsed() < BEEP_INTERVAL)
+        {
+            return;
+        }
+        self.last_beep = Some(Instant::now());
+        self.ring_bell = true;
+    }
+
+    /// Record how long the word the cursor is on took and how well it was typed when learning words
+    fn finish_word(&mut self) {
+        let word_instant = match self.word_instant {
+            Some(word_instant) => word_instant,
+            None => return,
+        };
+        let (expected, typed) = self.line.current_word();
+        if typed.is_empty() {
+            return;
+        }
+        let duration = word_instant.elapsed();
+        self.word_times.push((expected.to_string(), duration));
+        if let Some(learning) = &mut self.learning {
+            if learning.record(expected, expected == typed, duration) {
+                self.generator.learned.insert(expected.to_string());
+            } else {
+                self.generator.learned.remove(expected);
+            }
+        }
+        self.word_instant = Some(Instant::now());
+    }
+
+    /// Quit the test early
+    /// Sessions still show the stats of the drills finished so far
+    fn quit(&mut self) {
+        self.running = false;
+        // quitting is the only way to finish a zen test
+        self.show_final_score = self.session.is_some() || matches!(self.test_mode, TestMode::Zen);
+    }
+
+    /// Stop the test and exit without showing a summary or saving the unfinished test
+    fn interrupt(&mut self) {
+        self.running = false;
+        self.show_final_score = false;
+        self.interrupted = true;
+    }
+
+    /// Restart the test
+    fn reset(&mut self) -> crossterm::Result<()> {
+        self.previous_line = Line::EMPTY;
+        self.typed_lines.clear();
+        self._word_count = 0;
+        self._raw_word_count = 0;
+        self._keystrokes = 0;
+        self._errors = 0;
+        self._fixed = 0;
+        self._chars = 0;
+        self._char_count = 0;
+        self.stopwatch = None;
+        self.word_instant = None;
+        self.last_sample = (0f32, 0);
+        self.recent.clear();
+        self.skipped_lines = 0;
+        self.shown = Some(Instant::now());
+        self.reaction = None;
+        self.last_tab = None;
+        self.fetch_status = None;
+        let new_quote = matches!(self.test_mode, TestMode::QuoteMode { custom: None, .. })
+            // the same quote is typed again once there is one
+            && (!self.same_quote || self.quote.is_empty());
+        if new_quote {
+            let quotes = self.quotes.clone();
+            self.quote = quotes.quote_or_fallback(|status| self.set_fetch_status(status));
+        }
+        if let TestMode::QuoteMode { remaining, custom } = &mut self.test_mode {
+            *remaining = match custom {
+                Some(s) => s.clone(),
+                None => self.quote.clone(),
+            };
+        } else {
+            self.generator.mid_sentence = false;
+            // start over with a new quote
+            if let Some(remaining) = &mut self.endless_quotes {
+                remaining.clear();
+            }
+        }
+        self.line = self.new_line();
+        self.upcoming.clear();
+        self.fill_upcoming();
+        self.see_line();
+        self.start_line_details();
+        Ok(())
+    }
+
+    /// Run a single test until it is finished or quit
+    fn run_test(&mut self) -> crossterm::Result<()> {
+        self.render()?;
+        self.shown = Some(Instant::now());
+        while self.running {
+            let action = self.next_action()?;
+            let changed = self.update(action)?;
+            if let Some(stopwatch) = self.stopwatch {
+                let elapsed = stopwatch.elapsed().as_secs_f32();
+                self.recent.record(elapsed, self.word_count());
+                self.sample_wpm(elapsed);
+            }
+            if changed {
+                self.render()?;
+            }
+            // with --must-be-clean a reached word count only stops the test once the line has no mistakes
+            if self.check_stop().is_some() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the wpm typed since the last sample once a sample interval has passed
+    fn sample_wpm(&mut self, elapsed: f32) {
+        let (last, last_keystrokes) = self.last_sample;
+        if elapsed - last < SAMPLE_INTERVAL {
+            return;
+        }
+        let (keystrokes, _) = self.keystrokes();
+        if let Some(wpm) = char_wpm(keystrokes.saturating_sub(last_keystrokes), elapsed - last) {
+            self.wpm_samples.push(wpm);
+        }
+        self.last_sample = (elapsed, keystrokes);
+    }
+
+    /// Count down in the middle of the screen before the test begins
+    /// Input is ignored until the countdown finishes except for quitting
+    fn countdown(&mut self) -> crossterm::Result<()> {
+        for n in (1..=self.countdown).rev() {
+            let (width, height) = self.screen_size()?;
+            self.clear()?;
+            queue!(
+                self.stdout,
+                cursor::MoveTo(width / 2, height / 2),
+                Print(n.to_string().bold())
+            )?;
+            self.stdout.flush()?;
+            let deadline = Instant::now() + Duration::from_secs(1);
+            while Instant::now() < deadline {
+                match self.input.poll()?.and_then(Action::from_event) {
+                    Some(Action::Quit) => self.quit(),
+                    Some(Action::Interrupt) => self.interrupt(),
+                    _ => continue,
+                }
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Run tests until all attempts are finished or the user quits
+    /// Unlike `run` the terminal is left alone and nothing is printed once finished
+    pub fn run_attempts(&mut self) -> crossterm::Result<()> {
+        self.countdown()?;
+        while self.running {
+            self.run_test()?;
+            // an interrupted test is abandoned rather than finished
+            if let Some(result) = self.result().filter(|_| !self.interrupted) {
+                self.results.push(result);
+            }
+            if !self.running {
+                break;
+            }
+            if let Some(session) = &mut self.session {
+                if session.expired() {
+                    break;
+                }
+                self.test_mode = session.next_drill();
+                self.update_stop_conditions();
+            } else if self.results.len() as u32 >= self.attempts {
+                break;
+            }
+            self.keep_details();
+            self.reset()?;
+        }
+        Ok(())
+    }
+
+    /// Start the test application
+    pub fn run(&mut self) -> crossterm::Result<()> {
+        terminal::enable_raw_mode()?;
+        if let Some(caret) = self.caret {
+            queue!(self.stdout, SetCaret(caret))?;
+        }
+        if self.caret_char.is_some() {
+            queue!(self.stdout, cursor::Hide)?;
+        }
+        let result = self.run_with_end_screen();
+        self.restore_terminal()?;
+        result?;
+        self.save_results();
+        if self.show_final_score && self.json {
+            self.print_json();
+        } else if self.show_final_score {
+            for line in self.summary_lines() {
+                println!("{}", line);
+            }
+            if self.show_keymap {
+                println!("{}", keymap::render(&self.errors));
+            }
+            if self.show_transcript {
+                for line in self.transcript_lines() {
+                    println!("{}", line);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Save what was learned and log the finished results
+    /// Replays are not saved since they repeat a test that was already taken
+    fn save_results(&self) {
+        if self.replaying {
+            return;
+        }
+        if let Some(learning) = &self.learning {
+            if let Err(e) = learning.save() {
+                eprintln!("Could not save learned words because {}", e);
+            }
+        }
+        if let Some(coverage) = &self.coverage {
+            if let Err(e) = coverage.save() {
+                eprintln!("Could not save seen words because {}", e);
+            }
+        }
+        // include the results from before restarting at the summary screen
+        let all: Vec<TestResult> = self
+            .earlier_results
+            .iter()
+            .chain(&self.results)
+            .cloned()
+            .collect();
+        if !all.is_empty() {
+            match History::load() {
+                Ok(mut history) => {
+                    history.record(&all, export::now());
+                    if let Err(e) = history.save() {
+                        eprintln!("Could not save results because {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Could not save results. {}", e),
+            }
+        }
+        if let Some(path) = &self.csv {
+            if let Err(e) = export::append_csv(path, &all, export::now()) {
+                eprintln!("Could not log results to {} because {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Leave raw mode and put the caret back however the test finished
+    fn restore_terminal(&mut self) -> crossterm::Result<()> {
+        let cleared = self.clear().and_then(|()| self.stdout.flush());
+        // raw mode is left even if the screen could not be cleared
+        terminal::disable_raw_mode()?;
+        cleared?;
+        if self.caret.is_some() {
+            execute!(self.stdout, SetCaret(CaretStyle::Default))?;
+        }
+        if self.caret_char.is_some() {
+            execute!(self.stdout, cursor::Show)?;
+        }
+        Ok(())
+    }
+
+    /// Run tests and then show the summary screen until the user exits instead of restarting
+    fn run_with_end_screen(&mut self) -> crossterm::Result<()> {
+        // sessions change modes so they have no single personal best
+        // and replays repeat a test that was already compared to it
+        if self.session.is_none() && !self.replaying {
+            self.personal_best = History::load()
+                .ok()
+                .and_then(|history| history.best_wpm(&self.test_mode.to_string()));
+        }
+        loop {
+            self.run_attempts()?;
+            // sessions and JSON output are meant to be finished once they end
+            if !self.show_final_score || self.json || self.session.is_some() {
+                return Ok(());
+            }
+            match self.end_screen()? {
+                EndChoice::Exit => return Ok(()),
+                EndChoice::Restart => self.restart()?,
+            }
+        }
+    }
+
+    /// Draw the summary in the middle of the screen until a key chooses what to do next
+    fn end_screen(&mut self) -> crossterm::Result<EndChoice> {
+        self.draw_end_screen()?;
+        loop {
+            let action = self.next_action()?;
+            if let Some(choice) = EndChoice::from_action(action) {
+                return Ok(choice);
+            }
+            if let Action::Resize(columns, rows) = action {
+                self.resize(columns, rows);
+                self.draw_end_screen()?;
+            }
+        }
+    }
+
+    /// Draw each line of the summary centered on the screen along with the keys to press
+    fn draw_end_screen(&mut self) -> crossterm::Result<()> {
+        let (width, height) = self.screen_size()?;
+        let mut lines = self.summary_lines();
+        lines.push(String::new());
+        lines.push("Enter - Exit    Tab - Restart".into());
+        let top = height.saturating_sub(lines.len() as u16) / 2;
+        self.clear()?;
+        for (row, line) in lines.iter().enumerate() {
+            let x = width.saturating_sub(line::display_width(line) as u16) / 2;
+            queue!(
+                self.stdout,
+                cursor::MoveTo(x, top + row as u16),
+                Print(line)
+            )?;
+        }
+        self.stdout.flush()
+    }
+
+    /// Start over after the summary screen, keeping finished results so they are saved
+    /// A new personal best becomes the one to beat
+    fn restart(&mut self) -> crossterm::Result<()> {
+        if let (Some(pb), Some(best)) = (self.personal_best, self.best_wpm()) {
+            self.personal_best = Some(pb.max(best));
+        }
+        self.earlier_results.append(&mut self.results);
+        self.word_times.clear();
+        self.wpm_samples.clear();
+        self.errors.clear();
+        self.keep_details();
+        self.stop_reason = None;
+        self.running = true;
+        self.reset()
+    }
+
+    /// Keep the word times, wpm samples and missed keys of the attempts finished so far
+    fn keep_details(&mut self) {
+        self.kept_details = (
+            self.word_times.len(),
+            self.wpm_samples.len(),
+            self.errors.clone(),
+        );
+    }
+
+    /// Forget the word times, wpm samples and missed keys of the attempt being restarted
+    fn discard_details(&mut self) {
+        let (word_times, wpm_samples, errors) = &self.kept_details;
+        self.word_times.truncate(*word_times);
+        self.wpm_samples.truncate(*wpm_samples);
+        self.errors = errors.clone();
+    }
+
+    /// Remember the word times, missed keys and learning progress as the current line starts
+    fn start_line_details(&mut self) {
+        let details = (
+            self.word_times.len(),
+            self.errors.clone(),
+            self.learning.clone(),
+        );
+        self.previous_line_details = std::mem::replace(&mut self.line_details, details);
+    }
+
+    /// Forget the word times, missed keys and learning progress of the current line
+    fn discard_line_details(&mut self) {
+        let (word_times, errors, learning) = self.line_details.clone();
+        self.word_times.truncate(word_times);
+        self.errors = errors;
+        if let Some(learning) = learning {
+            self.generator.learned = learning.graduated();
+            self.learning = Some(learning);
+        }
+    }
+
+    /// Whether or not the finished tests were slower than the target wpm
+    pub fn missed_target(&self) -> bool {
+        match (self.target_wpm, total(&self.results, "").wpm()) {
+            (Some(target), Some(wpm)) => wpm < target as f32,
+            _ => false,
+        }
+    }
+
+    /// Print the combined result of every test as a JSON object
+    fn print_json(&self) {
+        if self.results.is_empty() {
+            return;
+        }
+        let mode = if self.session.is_some() {
+            "session".into()
+        } else {
+            self.test_mode.to_string()
+        };
+        let summary = Summary::from(&total(&self.results, &mode));
+        match serde_json::to_string(&summary) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Could not print the result as JSON because {}", e),
+        }
+    }
+
+    /// Describe the finished tests in lines of text without drawing anything
+    fn summary_lines(&self) -> Vec<String> {
+        let precision = self.precision;
+        let mut lines = if self.session.is_some() {
+            self.session_lines()
+        } else if self.attempts > 1 {
+            self.attempt_lines()
+        } else if let Some(stopwatch) = self.stopwatch {
+            let elapsed = stopwatch.elapsed().as_secs_f32();
+            let wc = self.word_count();
+            let mut lines = vec![format!(
+                "You typed {} words {:.*} seconds",
+                wc, precision, elapsed
+            )];
+            lines.push(
+                match (wpm(self.raw_word_count(), elapsed), wpm(wc, elapsed)) {
+                    (Some(raw), Some(net)) => format!(
+                        "Raw: {:.*} wpm, Net: {:.*} wpm",
+                        precision, raw, precision, net
+                    ),
+                    _ => "That was too fast to measure wpm".into(),
+                },
+            );
+            // correct characters still count when words are unfinished
+            let chars = self.char_count();
+            if let Some(char_wpm) = char_wpm(chars, elapsed).filter(|_| elapsed >= MIN_ELAPSED) {
+                lines.push(format!(
+                    "Characters: {} correct, {:.*} wpm",
+                    chars, precision, char_wpm
+                ));
+            }
+            if let Some(accuracy) = self.accuracy() {
+                lines.push(format!("Accuracy: {:.*}%", precision, accuracy));
+            }
+            lines.extend(self.keystroke_lines());
+            lines
+        } else {
+            Vec::new()
+        };
+        if let Some(consistency) = consistency(&self.wpm_samples) {
+            lines.push(format!("Consistency: {:.*}%", precision, consistency));
+        }
+        if !self.wpm_samples.is_empty() {
+            lines.push(format!("Speed: {}", sparkline(&self.wpm_samples)));
+        }
+        if let Some(reaction) = self.reaction {
+            lines.push(format!(
+                "Reaction: {:.*}s",
+                precision,
+                reaction.as_secs_f32()
+            ));
+        }
+        if self.skipped_lines > 0 {
+            lines.push(format!("Skipped lines: {}", self.skipped_lines));
+        }
+        if let Some(reason) = self.stop_reason {
+            lines.push(format!("Ended because: {}", reason));
+        }
+        if let Some(coverage) = &self.coverage {
+            lines.push(describe_coverage(coverage, &self.generator.words));
+        }
+        lines.extend(self.slowest_word_lines());
+        lines.extend(self.missed_key_line());
+        lines.extend(self.personal_best_line());
+        lines
+    }
+
+    /// Get the highest wpm of the finished tests
+    fn best_wpm(&self) -> Option<f32> {
+        self.results
+            .iter()
+            .filter_map(TestResult::wpm)
+            .max_by(f32::total_cmp)
+    }
+
+    /// Celebrate beating the personal best or say how far away it is
+    fn personal_best_line(&self) -> Option<String> {
+        let pb = self.personal_best?;
+        let precision = self.precision;
+        let best = self.best_wpm();
+        Some(if is_new_best(Some(pb), best) {
+            format!(
+                "New personal best! {:.*} wpm beats {:.*} wpm",
+                precision,
+                best.unwrap_or_default(),
+                precision,
+                pb
+            )
+        } else {
+            format!("Personal best: {:.*} wpm", precision, pb)
+        })
+    }
+
+    /// Describe the keys that were mistyped the most if there were any mistakes
+    fn missed_key_line(&self) -> Option<String> {
+        let top = keymap::top_errors(&self.errors, MISSED_KEYS);
+        if top.is_empty() {
+            return None;
+        }
+        let keys = top
+            .iter()
+            .map(|(key, errors)| format!("{} ({})", keymap::key_name(*key), errors))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(format!("Most missed keys: {}", keys))
+    }
+
+    /// Describe the words that took the longest to type with a bar for each one's wpm
+    fn slowest_word_lines(&self) -> Vec<String> {
+        let slowest = slowest_words(&self.word_times, SLOWEST_WORDS);
+        let fastest = match slowest.last() {
+            Some((_, wpm)) => *wpm,
+            None => return Vec::new(),
+        };
+        let width = slowest
+            .iter()
+            .map(|(word, _)| word.len())
+            .max()
+            .unwrap_or(0);
+        let mut lines = vec!["Slowest words:".to_string()];
+        for (word, wpm) in slowest {
+            let bar = "█".repeat((wpm / fastest * 20f32).round() as usize);
+            lines.push(format!(
+                "  {:width$} {} wpm {}",
+                word,
+                format_metric(Some(wpm), 6, self.precision),
+                bar
+            ));
+        }
+        lines
+    }
+
+    /// Describe the words, time, wpm, and accuracy of a result
+    fn describe(&self, result: &TestResult) -> String {
+        let precision = self.precision;
+        let wpm = match (result.wpm(), result.raw_wpm()) {
+            (Some(wpm), Some(raw)) => {
+                format!("{:.*} wpm, {:.*} raw", precision, wpm, precision, raw)
+            }
+            _ => "too fast to measure".into(),
+        };
+        let accuracy = match result.accuracy() {
+            Some(accuracy) => format!("{:.*}%", precision, accuracy),
+            None => "--".into(),
+        };
+        format!(
+            "{} words {:.*} seconds ({}, {} accuracy)",
+            result.words, precision, result.elapsed, wpm, accuracy
+        )
+    }
+
+    /// Describe the combined results of each kind of drill and of the whole session
+    fn session_lines(&self) -> Vec<String> {
+        let overall = total(&self.results, "Overall");
+        aggregate(&self.results)
+            .iter()
+            .chain([&overall])
+            .map(|result| format!("{}: {}", result.mode, self.describe(result)))
+            .collect()
+    }
+
+    /// Describe the results of each attempt and the improvement across them
+    fn attempt_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .results
+            .iter()
+            .enumerate()
+            .map(|(i, result)| format!("Attempt {}: {}", i + 1, self.describe(result)))
+            .collect();
+        let average = average(&self.results);
+        if let Some(wpm) = average.wpm {
+            let accuracy = match average.accuracy {
+                Some(accuracy) => format!("{:.*}%", self.precision, accuracy),
+                None => "--".into(),
+            };
+            lines.push(format!(
+                "Average: {:.*} wpm, {} accuracy",
+                self.precision, wpm, accuracy
+            ));
+        }
+        if let Some(change) = improvement(&self.results) {
+            lines.push(format!(
+                "Improvement: {:+.*} wpm from first to last attempt",
+                self.precision, change
+            ));
+        }
+        lines
+    }
+}
+
+/// The shortest time between rings of the bell when mistakes are typed
+const BEEP_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long after the first Tab a second Tab restarts the test with --confirm-restart
+const RESTART_WINDOW: Duration = Duration::from_millis(1500);
+
+/// The number of seconds between wpm samples used to measure consistency
+const SAMPLE_INTERVAL: f32 = 1.0;
+
+/// Show how fetching a quote is going on the current line of {out}
+/// The line is cleared once the quote is fetched and failures stay on their own line
+/// Only used before the test is drawn since it prints wherever the cursor is
+fn show_fetch_status(out: &mut impl Write, status: FetchStatus) -> io::Result<()> {
+    queue!(
+        out,
+        Print('\r'),
+        terminal::Clear(terminal::ClearType::CurrentLine)
+    )?;
+    match status {
+        FetchStatus::Fetching => queue!(out, Print(status))?,
+        FetchStatus::Fetched => (),
+        FetchStatus::Failed(_) => queue!(out, Print(status), Print("\r\n"))?,
+    }
+    out.flush()
+}
+
+/// Get a function that shows each fetch status on {out}
+/// The status is only informative so failing to show it is ignored
+fn report_to(out: &mut impl Write) -> impl FnMut(FetchStatus) + '_ {
+    move |status| {
+        let _ = show_fetch_status(out, status);
+    }
+}
+
+/// The time between the test being shown and the first keystroke
+fn reaction_time(shown: Instant, first_key: Instant) -> Duration {
+    first_key.saturating_duration_since(shown)
+}
+
+/// Draw a bar {width} characters wide that is filled up to {fraction} followed by the percentage
+fn progress_bar(fraction: f32, width: usize) -> String {
+    let fraction = fraction.clamp(0f32, 1f32);
+    let filled = (fraction * width as f32).round() as usize;
+    format!(
+        "[{}{}] {}%",
+        "#".repeat(filled),
+        "-".repeat(width - filled),
+        (fraction * 100f32).floor()
+    )
+}
+
+/// Get the number of columns before {line} when drawn within {width} columns
+/// Lines are only padded when they are centered
+fn padding(line: &Line, width: usize, center: bool) -> u16 {
+    if center {
+        center_padding(line.drawn_width(), width) as u16
+    } else {
+        0
+    }
+}
+
+/// Replace {remaining} with a new quote once all of it has been used
+fn refill(remaining: &mut String, quote: impl FnOnce() -> String) {
+    if remaining.is_empty() {
+        *remaining = quote();
+    }
+}
+
+/// The number of lines printed by --preview
+const PREVIEW_LINES: usize = 5;
+
+/// The number of words shown when printing the slowest words
+const SLOWEST_WORDS: usize = 5;
+
+/// The number of most recent results the trend is measured over
+const TREND_RUNS: usize = 10;
+
+/// The smallest change in wpm that counts as improving or declining
+const STEADY_WPM: f32 = 1.0;
+
+/// The number of characters in the progress bar
+const PROGRESS_WIDTH: usize = 10;
+
+/// The number of keys shown when printing the most missed keys
+const MISSED_KEYS: usize = 5;
+
+/// Get the longest line that fits on a screen with {columns} columns
+fn fit_width(columns: u16) -> usize {
+    // leave room for the cursor after the last character
+    (columns as usize).saturating_sub(1)
+}
+
+/// Describe the percentage of the {words} being used that has been seen
+fn describe_coverage(coverage: &Coverage, words: &[String]) -> String {
+    format!(
+        "Coverage: {:.0}% of word list seen",
+        coverage.percent(words)
+    )
+}
+
+/// Describe statistics about the results from previous sessions
+fn describe_stats(stats: &Stats, precision: usize) -> Vec<String> {
+    if stats.tests == 0 {
+        return vec!["There are no results yet".into()];
+    }
+    let mut lines = vec![
+        format!("Tests: {}", stats.tests),
+        format!(
+            "Average wpm: {}",
+            format_metric(stats.average.wpm, 0, precision)
+        ),
+        format!("Best wpm: {}", format_metric(stats.best, 0, precision)),
+        format!(
+            "Average accuracy: {}%",
+            format_metric(stats.average.accuracy, 0, precision)
+        ),
+    ];
+    if let Some(trend) = stats.trend {
+        let direction = if trend.abs() < STEADY_WPM {
+            "steady"
+        } else if trend > 0f32 {
+            "improving"
+        } else {
+            "declining"
+        };
+        lines.push(format!(
+            "Trend: {} ({:+.*} wpm over the last {} tests)",
+            direction,
+            precision,
+            trend,
+            stats.tests.min(TREND_RUNS)
+        ));
+    }
+    lines
+}
+
+/// Print the best results from previous sessions
+fn print_leaderboard(history: &History, mode: Option<&str>, precision: usize) {
+    let records = history.leaderboard(mode, LEADERBOARD_LEN);
+    if records.is_empty() {
+        println!("There are no results yet");
+    }
+    for (place, record) in records.iter().enumerate() {
+        let result = &record.result;
+        println!(
+            "{:>2}. {} wpm  {}% accuracy  {}",
+            place + 1,
+            format_metric(result.wpm(), 6, precision),
+            format_metric(result.accuracy(), 6, precision),
+            result.mode
+        );
+    }
+}
+
+/// Make sure a word count or time limit would not end a test before it starts
+fn validate_limits(number: Option<u32>, time: Option<u64>) -> Result<(), String> {
+    if number == Some(0) {
+        return Err("The number of words must be at least 1.".into());
+    }
+    if time == Some(0) {
+        return Err("The time limit must be at least 1 second.".into());
+    }
+    Ok(())
+}
+
+/// Make sure the word length limits leave some words to generate lines from
+fn validate_word_lengths(args: &Args) -> Result<(), String> {
+    let (min, max) = (args.min_word_length, args.max_word_length);
+    if min.is_none() && max.is_none() {
+        return Ok(());
+    }
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(format!(
+                "The minimum word length {} is longer than the maximum word length {}.",
+                min, max
+            ));
+        }
+    }
+    word_list(args).map(drop)
+}
+
+/// Make sure the test modes in {args} do not conflict
+/// This is checked again after the menu sets the mode
+fn validate_modes(args: &Args) -> Result<(), String> {
+    // --time and --number may be combined to stop at whichever comes first
+    let limited = args.time.is_some() || args.number.is_some();
+    if limited && args.quote
+        || args.zen && (limited || args.quote)
+        || args.endless_quotes && (args.quote || args.zen || args.session.is_some())
+        || args.code && (args.quote || args.zen || args.endless_quotes)
+    {
+        return Err("Invalid combination of flags. Please do not pass conflicting flags.".into());
+    }
+    Ok(())
+}
+
+/// Get the words lines are generated from, which are the word file or language limited by length and difficulty
+fn word_list(args: &Args) -> Result<Vec<String>, String> {
+    let words = match &args.custom_words {
+        Some(words) => words.clone(),
+        None => line::language_words(&args.language)?
+            .iter()
+            .map(|word| word.to_string())
+            .collect(),
+    };
+    let words = line::filter_lengths(&words, args.min_word_length, args.max_word_length)?;
+    Ok(match args.difficulty {
+        Some(difficulty) => line::filter_words(&words, difficulty),
+        None => words,
+    })
+}
+
+/// Parse and validate the command-line arguments
+pub fn parse_args<I>(iter: I) -> Result<Args, Outcome>
+where
+    I: IntoIterator,
+    I::Item: Into<std::ffi::OsString> + Clone,
+{
+    let mut args = match Args::from_iter_safe(iter) {
+        Ok(args) => args,
+        Err(e) if !e.use_stderr() => {
+            println!("{}", e.message);
+            return Err(Outcome::Success);
+        }
+        Err(e) => return Err(Outcome::InvalidArgs(e.message)),
+    };
+    if let Some(path) = &args.quote_file {
+        if args.custom_quote.is_some() {
+            return Err(Outcome::InvalidArgs(
+                "Please pass either a custom quote or a quote file, not both.".into(),
+            ));
+        }
+        args.custom_quote = Some(quote::read_quote(path).map_err(Outcome::InvalidArgs)?);
+    }
+    if let Some(path) = &args.replay {
+        // replay against the same text the keystrokes were recorded with
+        let (header, keystrokes) = record::read_recording(path).map_err(Outcome::InvalidArgs)?;
+        args.seed = header.seed.or(args.seed);
+        args.custom_quote = header.quote.or(args.custom_quote);
+        args.replay_keys = keystrokes;
+    }
+    if args.record.is_some() {
+        if args.endless_quotes {
+            return Err(Outcome::InvalidArgs(
+                "Endless quotes cannot be recorded because they are fetched while typing.".into(),
+            ));
+        }
+        // save a seed so the same words can be generated when replaying
+        args.seed.get_or_insert_with(rand::random);
+    }
+    if let Some(quote) = &mut args.custom_quote {
+        *quote = quote::normalize(quote);
+        if quote.is_empty() {
+            return Err(Outcome::InvalidArgs("The custom quote is empty.".into()));
+        }
+        args.quote = true;
+    }
+    line::language_words(&args.language).map_err(Outcome::InvalidArgs)?;
+    if let Some(path) = &args.word_file {
+        args.custom_words = Some(line::read_words(path).map_err(Outcome::InvalidArgs)?);
+    }
+    if let Some(path) = &args.words_file_weighted {
+        let (words, weights) = line::read_weighted_words(path).map_err(Outcome::InvalidArgs)?;
+        args.custom_words = Some(words);
+        args.word_weights = weights;
+    }
+    validate_limits(args.number, args.time).map_err(Outcome::InvalidArgs)?;
+    validate_word_lengths(&args).map_err(Outcome::InvalidArgs)?;
+    if args.quote_timeout == 0 {
+        return Err(Outcome::InvalidArgs(
+            "The quote timeout must be at least 1 second.".into(),
+        ));
+    }
+    if args.replay_speed.is_nan() || args.replay_speed <= 0.0 {
+        return Err(Outcome::InvalidArgs(
+            "The replay speed must be greater than 0.".into(),
+        ));
+    }
+    if args.lookahead == 0 {
+        return Err(Outcome::InvalidArgs(
+            "The lookahead must be at least 1 line.".into(),
+        ));
+    }
+    if args.precision > MAX_PRECISION {
+        return Err(Outcome::InvalidArgs(format!(
+            "The precision must be between 0 and {}.",
+            MAX_PRECISION
+        )));
+    }
+    if args.session.is_some()
+        && (args.time.is_some()
+            || args.number.is_some()
+            || args.quote
+            || args.zen
+            || args.repeat_quote.is_some()
+            || args.repeat.is_some())
+    {
+        return Err(Outcome::InvalidArgs(
+            "The --session flag cannot be combined with other test modes.".into(),
+        ));
+    }
+    if args.menu
+        && (args.time.is_some()
+            || args.number.is_some()
+            || args.quote
+            || args.zen
+            || args.endless_quotes
+            || args.session.is_some())
+    {
+        return Err(Outcome::InvalidArgs(
+            "The --menu flag cannot be combined with other test modes.".into(),
+        ));
+    }
+    if args.caret.is_some() && args.caret_char.is_some() {
+        return Err(Outcome::InvalidArgs(
+            "Please pass either --caret or --caret-char, not both.".into(),
+        ));
+    }
+    if args.repeat_quote.is_some() && args.repeat.is_some() {
+        return Err(Outcome::InvalidArgs(
+            "Please pass either --repeat or --repeat-quote, not both.".into(),
+        ));
+    }
+    if args.repeat_quote.is_some() && args.custom_quote.is_none() {
+        return Err(Outcome::InvalidArgs(
+            "The --repeat-quote flag requires a custom quote.".into(),
+        ));
+    }
+    validate_modes(&args).map_err(Outcome::InvalidArgs)?;
+    Ok(args)
+}
+
+/// Show the menu and set the test mode in the arguments to the chosen one
+fn choose_mode(args: &mut Args) -> crossterm::Result<menu::State> {
+    let mut menu = Menu::default();
+    terminal::enable_raw_mode()?;
+    let result = menu.run(&mut TerminalInput, &mut io::stdout());
+    terminal::disable_raw_mode()?;
+    let state = result?;
+    menu.apply(args);
+    Ok(state)
+}
+
+/// Run the application and report how it finished
+pub fn start() -> Outcome {
+    if let Err(e) = line::validate_word_list() {
+        return Outcome::RuntimeError(e);
+    }
+    let mut args = match parse_args(std::env::args_os()) {
+        Ok(args) => args,
+        Err(outcome) => return outcome,
+    };
+    match Config::load() {
+        Ok(config) => config.apply(&mut args),
+        Err(e) => return Outcome::InvalidArgs(e),
+    }
+    if args.coverage {
+        return match Coverage::load().and_then(|coverage| Ok((coverage, word_list(&args)?))) {
+            Ok((coverage, words)) => {
+                println!("{}", describe_coverage(&coverage, &words));
+                Outcome::Success
+            }
+            Err(e) => Outcome::RuntimeError(e),
+        };
+    }
+    if args.stats || args.leaderboard {
+        let history = match History::load() {
+            Ok(history) => history,
+            Err(e) => return Outcome::RuntimeError(e),
+        };
+        if args.stats {
+            for line in describe_stats(&history.stats(TREND_RUNS), args.precision) {
+                println!("{}", line);
+            }
+        } else {
+            print_leaderboard(&history, args.mode.as_deref(), args.precision);
+        }
+        return Outcome::Success;
+    }
+    if args.list_languages {
+        for (name, _) in line::LANGUAGES {
+            println!("{}", name);
+        }
+        return Outcome::Success;
+    }
+    if args.menu {
+        match choose_mode(&mut args) {
+            Ok(menu::State::Started) => (),
+            Ok(_) => return Outcome::Success,
+            Err(e) => return Outcome::RuntimeError(e.to_string()),
+        }
+        if let Err(e) = validate_modes(&args) {
+            return Outcome::InvalidArgs(e);
+        }
+    }
+    let preview = args.preview;
+    let mut test = match TypingTest::new(args) {
+        Ok(test) => test,
+        Err(e) => return Outcome::RuntimeError(e),
+    };
+    if preview {
+        for line in test.preview(PREVIEW_LINES) {
+            println!("{}", line);
+        }
+        return Outcome::Success;
+    }
+    match test.run() {
+        Ok(()) if test.missed_target() => Outcome::TargetMissed,
+        Ok(()) => Outcome::Success,
+        Err(e) => Outcome::RuntimeError(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn args_precision_test() {
+        let args = Args::from_iter(["typing_test"]);
+        assert_eq!(args.precision, 2);
+        let args = Args::from_iter(["typing_test", "--precision", "4"]);
+        assert_eq!(args.precision, 4);
+        assert!(Args::from_iter_safe(["typing_test", "--precision", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parse_args_test() {
+        assert!(parse_args(["typing_test", "-t", "30"]).is_ok());
+        assert!(parse_args(["typing_test", "-t", "30", "-n", "10"]).is_ok());
+        for invalid in [
+            &["typing_test", "-t", "30", "-q"][..],
+            &["typing_test", "--precision", "5"],
+            &["typing_test", "--repeat-quote", "3"],
+            &["typing_test", "--session", "10", "-n", "50"],
+            &["typing_test", "--not-a-flag"],
+            &["typing_test", "--zen", "-t", "30"],
+            &["typing_test", "--zen", "-n", "30"],
+            &["typing_test", "--zen", "-q"],
+            &["typing_test", "--endless-quotes", "-q"],
+            &["typing_test", "--caret", "bar", "--caret-char", "|"],
+            &["typing_test", "--quote-timeout", "0"],
+            &["typing_test", "-t", "0"],
+            &["typing_test", "-n", "0"],
+            &["typing_test", "-n", "0", "-t", "30"],
+            &["typing_test", "--code", "-q"],
+            &["typing_test", "--menu", "-t", "30"],
+            &["typing_test", "--menu", "-c", "quote"],
+            &["typing_test", "--record", "keys.jsonl", "--endless-quotes"],
+            &["typing_test", "--language", "klingon"],
+            &["typing_test", "--word-file", "does/not/exist"],
+            &["typing_test", "--min-word-length", "100"],
+            &[
+                "typing_test",
+                "--min-word-length",
+                "6",
+                "--max-word-length",
+                "4",
+            ],
+            &["typing_test", "--words-file-weighted", "does/not/exist"],
+            &[
+                "typing_test",
+                "--word-file",
+                "words.txt",
+                "--words-file-weighted",
+                "words.tsv",
+            ],
+            &["typing_test", "--mode", "quote"],
+            &["typing_test", "--repeat", "3", "--session", "5"],
+            &[
+                "typing_test",
+                "--repeat",
+                "3",
+                "--repeat-quote",
+                "3",
+                "-c",
+                "quote",
+            ],
+            &["typing_test", "--error-color", "#nothex"],
+        ] {
+            let outcome = parse_args(invalid).unwrap_err();
+            assert_eq!(outcome.exit_code(), 2, "{:?}", outcome);
+        }
+        assert_eq!(
+            parse_args(["typing_test", "-h"]).unwrap_err(),
+            Outcome::Success
+        );
+    }
+
+    /// Create a typing test from command-line arguments
+    fn typing_test(args: &[&str]) -> TypingTest {
+        let args = Args::from_iter(["typing_test"].iter().chain(args));
+        TypingTest::new(args).unwrap()
+    }
+
+    #[test]
+    fn trim_leading_space_test() {
+        let mut test = typing_test(&[]);
+        assert!(test.type_char(' '));
+        assert_eq!(test.line.index(), 1);
+        assert!(test.stopwatch.is_some());
+        let mut test = typing_test(&["--trim-leading-space"]);
+        assert!(!test.type_char(' '));
+        assert_eq!(test.line.index(), 0);
+        assert!(test.stopwatch.is_none());
+        test.type_char('a');
+        test.type_char(' ');
+        assert_eq!(test.line.index(), 2);
+    }
+
+    #[test]
+    fn seed_test() {
+        let a = typing_test(&["--seed", "7", "--width", "60"]);
+        let b = typing_test(&["--seed", "7", "--width", "60"]);
+        assert_eq!(a.line.expected(), b.line.expected());
+        assert_eq!(a.upcoming[0].expected(), b.upcoming[0].expected());
+    }
+
+    #[test]
+    fn pause_test() {
+        let mut test = typing_test(&[]);
+        test.update(Action::Pause).unwrap();
+        assert!(test.stopwatch.is_none());
+        test.update(Action::Type('a')).unwrap();
+        test.update(Action::Pause).unwrap();
+        assert!(test.stopwatch.unwrap().paused());
+        test.update(Action::Pause).unwrap();
+        assert!(!test.stopwatch.unwrap().paused());
+        test.update(Action::Pause).unwrap();
+        // typing resumes the timer
+        test.update(Action::Type('b')).unwrap();
+        assert!(!test.stopwatch.unwrap().paused());
+    }
+
+    fn type_str(test: &mut TypingTest, s: &str) {
+        for ch in s.chars() {
+            test.type_char(ch);
+        }
+    }
+
+    #[test]
+    fn skip_line_test() {
+        let mut test = typing_test(&["-n", "100"]);
+        let next = test.upcoming[0].expected().to_string();
+        let word = test.line.current_word().0.to_string();
+        type_str(&mut test, &(word + " #"));
+        assert_eq!(test.word_times.len(), 1);
+        assert!(!test.errors.is_empty());
+        test.update(Action::Skip).unwrap();
+        // the skipped line leaves nothing behind in the details
+        assert!(test.word_times.is_empty());
+        assert!(test.errors.is_empty());
+        assert_eq!(test.line.expected(), next);
+        assert_eq!(test.line.typed_len(), 0);
+        assert_eq!(test.word_count(), 0);
+        assert_eq!(test.keystrokes(), (0, 0));
+        assert_eq!(test.skipped_lines, 1);
+        // a skipped line cannot be backspaced into
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.line.expected(), next);
+        assert!(test
+            .summary_lines()
+            .contains(&"Skipped lines: 1".to_string()));
+        test.reset().unwrap();
+        assert_eq!(test.skipped_lines, 0);
+    }
+
+    #[test]
+    fn skip_line_keeps_earlier_lines_test() {
+        let mut test = typing_test(&["-n", "100"]);
+        let first = test.line.expected().to_string();
+        type_str(&mut test, &(first.clone() + " "));
+        let (word_times, errors) = (test.word_times.len(), test.errors.clone());
+        assert_eq!(word_times, first.split(' ').count());
+        type_str(&mut test, "#");
+        test.update(Action::Skip).unwrap();
+        assert_eq!(test.word_times.len(), word_times);
+        assert_eq!(test.errors, errors);
+    }
+
+    #[test]
+    fn skip_quote_line_test() {
+        let quote = (0..25).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let mut test = typing_test(&["-q", "-c", &quote]);
+        test.update(Action::Skip).unwrap();
+        assert_eq!(test.line.expected(), "10 11 12 13 14 15 16 17 18 19");
+        assert_eq!(test.upcoming[0].expected(), "20 21 22 23 24");
+        assert_eq!(test.word_count(), 0);
+        // the skipped line is part of the quote that is complete
+        let remaining = String::new();
+        let (done, total) = test.quote_progress(&remaining);
+        assert_eq!(done, "0 1 2 3 4 5 6 7 8 9 ".len());
+        assert_eq!(total, quote.len());
+    }
+
+    #[test]
+    fn remaining_test() {
+        let mut test = typing_test(&["-n", "10"]);
+        assert_eq!(test.remaining(), Some(("Remaining", "10 words".into())));
+        let word = test.line.current_word().0.to_string();
+        type_str(&mut test, &(word + " "));
+        assert_eq!(test.remaining(), Some(("Remaining", "9 words".into())));
+        let mut test = typing_test(&["-t", "30", "--precision", "1"]);
+        assert_eq!(test.remaining(), Some(("Remaining", "30.0s".into())));
+        test.stopwatch = Some(Stopwatch::start());
+        test.stopwatch.as_mut().unwrap().pause();
+        assert_eq!(test.remaining(), Some(("Remaining", "30.0s".into())));
+        let test = typing_test(&["-t", "30", "-n", "10", "--precision", "0"]);
+        assert_eq!(test.test_mode.to_string(), "10 words or 30 seconds");
+        assert_eq!(
+            test.remaining(),
+            Some(("Remaining", "10 words or 30s".into()))
+        );
+        let mut test = typing_test(&["-q", "-c", "one two three"]);
+        assert_eq!(test.remaining(), Some(("Complete", "0%".into())));
+        type_str(&mut test, "one two");
+        assert_eq!(test.remaining(), Some(("Complete", "53%".into())));
+        type_str(&mut test, " three");
+        assert_eq!(test.remaining(), Some(("Complete", "100%".into())));
+    }
+
+    #[test]
+    fn progress_bar_test() {
+        assert_eq!(progress_bar(0f32, 10), "[----------] 0%");
+        assert_eq!(progress_bar(0.5, 10), "[#####-----] 50%");
+        assert_eq!(progress_bar(1f32, 10), "[##########] 100%");
+        assert_eq!(progress_bar(1.5, 4), "[####] 100%");
+    }
+
+    #[test]
+    fn completed_test() {
+        let mut test = typing_test(&["-n", "2"]);
+        assert_eq!(test.completed(), Some(0f32));
+        let word = test.line.current_word().0.to_string();
+        type_str(&mut test, &(word + " "));
+        assert_eq!(test.completed(), Some(0.5));
+        let mut test = typing_test(&["-q", "-c", "ab cd"]);
+        type_str(&mut test, "ab");
+        assert_eq!(test.completed(), Some(0.4));
+        let test = typing_test(&["-t", "30"]);
+        assert_eq!(test.completed(), Some(0f32));
+        assert_eq!(typing_test(&["--zen"]).completed(), None);
+    }
+
+    #[test]
+    fn same_quote_test() {
+        let mut test = typing_test(&["-q", "--offline", "--same-quote"]);
+        let expected = test.line.expected().to_string();
+        type_str(&mut test, "abc");
+        test.update(Action::Reset).unwrap();
+        assert_eq!(test.line.expected(), expected);
+        assert_eq!(test.line.index(), 0);
+        // without the flag the quote is replaced on restart
+        let mut test = typing_test(&["-q", "--offline"]);
+        test.quote.clear();
+        test.update(Action::Reset).unwrap();
+        assert!(!test.quote.is_empty());
+        assert!(test.quote.starts_with(test.line.expected()));
+    }
+
+    #[test]
+    fn word_times_test() {
+        let mut test = typing_test(&["-n", "10"]);
+        let first = test.line.current_word().0.to_string();
+        type_str(&mut test, &(first.clone() + " "));
+        let second = test.line.current_word().0.to_string();
+        type_str(&mut test, "x ");
+        let words: Vec<&str> = test
+            .word_times
+            .iter()
+            .map(|(word, _)| word.as_str())
+            .collect();
+        assert_eq!(words, [first.as_str(), second.as_str()]);
+        // a space without typing the word does not count
+        test.type_char(' ');
+        assert_eq!(test.word_times.len(), 2);
+    }
+
+    #[test]
+    fn sample_wpm_test() {
+        let mut test = typing_test(&["-n", "10"]);
+        type_str(&mut test, "abcde");
+        test.sample_wpm(0.5);
+        assert!(test.wpm_samples.is_empty());
+        test.sample_wpm(1.0);
+        assert_eq!(test.wpm_samples, [60.0]);
+        type_str(&mut test, "abcdefghij");
+        test.sample_wpm(3.0);
+        assert_eq!(test.wpm_samples, [60.0, 60.0]);
+        assert!(test.summary_lines().contains(&"Speed: ▄▄".to_string()));
+    }
+
+    #[test]
+    fn reset_details_test() {
+        let mut test = typing_test(&["-n", "10"]);
+        type_str(&mut test, "#### #### ");
+        test.sample_wpm(1.0);
+        assert_eq!(test.word_times.len(), 2);
+        assert_eq!(test.wpm_samples.len(), 1);
+        assert!(!test.errors.is_empty());
+        test.update(Action::Reset).unwrap();
+        let first = test.line.current_word().0.to_string();
+        type_str(&mut test, &(first.clone() + " "));
+        let lines = test.summary_lines();
+        assert!(
+            !lines.iter().any(|line| line.starts_with("Speed:")),
+            "{:?}",
+            lines
+        );
+        assert!(
+            !lines
+                .iter()
+                .any(|line| line.starts_with("Most missed keys:")),
+            "{:?}",
+            lines
+        );
+        assert_eq!(test.word_times.len(), 1);
+        assert_eq!(test.word_times[0].0, first);
+        // the details of finished attempts are kept when a later attempt is restarted
+        test.sample_wpm(1.0);
+        test.keep_details();
+        test.reset().unwrap();
+        type_str(&mut test, "#### ");
+        test.sample_wpm(1.0);
+        assert_eq!(test.word_times.len(), 2);
+        test.update(Action::Reset).unwrap();
+        assert_eq!(test.word_times.len(), 1);
+        assert_eq!(test.wpm_samples.len(), 1);
+        assert!(test.errors.is_empty());
+    }
+
+    #[test]
+    fn strict_test() {
+        let mut test = typing_test(&["-n", "10", "--strict"]);
+        let word = test.line.current_word().0.to_string();
+        assert!(!test.type_char(' '));
+        type_str(&mut test, &(word + "x"));
+        assert!(!test.type_char(' '));
+        test.update(Action::Backspace).unwrap();
+        assert!(test.type_char(' '));
+        assert_eq!(test.word_count(), 1);
+    }
+
+    #[test]
+    fn beep_test() {
+        let mut test = typing_test(&["-n", "10", "--beep"]);
+        let expected = test.line.next_expected().unwrap();
+        test.type_char(expected);
+        assert!(!test.ring_bell);
+        test.type_char('\t');
+        assert!(test.ring_bell);
+        // held keys only ring once until the interval passes
+        test.ring_bell = false;
+        test.type_char('\t');
+        assert!(!test.ring_bell);
+        let mut test = typing_test(&["-n", "10"]);
+        test.type_char('\t');
+        assert!(!test.ring_bell);
+    }
+
+    #[test]
+    fn pace_columns_test() {
+        let mut test = typing_test(&["-n", "50", "--target-wpm", "60"]);
+        // 60 wpm is 5 characters a second
+        assert_eq!(test.pace_columns(0.0), (Some(0), None));
+        assert_eq!(test.pace_columns(1.0), (Some(5), None));
+        // the pacer moves to the next line once it passes the current one
+        let line_len = test.line.expected().chars().count() + 1;
+        let seconds = line_len as f32 / 5.0;
+        assert_eq!(test.pace_columns(seconds), (None, Some(0)));
+        assert_eq!(test.pace_columns(seconds + 1.0), (None, Some(5)));
+        // it is hidden once it falls behind the current line
+        test._chars = 10;
+        assert_eq!(test.pace_columns(1.0), (None, None));
+        assert_eq!(test.pace_columns(3.0), (Some(5), None));
+        assert_eq!(typing_test(&["-n", "50"]).pace_columns(1.0), (None, None));
+    }
+
+    #[test]
+    fn resize_test() {
+        let mut test = typing_test(&["-n", "50"]);
+        test.set_screen(io::sink(), (80, 24));
+        test.set_input(ScriptedInput::new([Event::Resize(40, 10)]));
+        let action = test.next_action().unwrap();
+        assert_eq!(action, Action::Resize(40, 10));
+        assert!(test.update(action).unwrap());
+        assert_eq!(test.screen_size().unwrap(), (40, 10));
+        assert_eq!(test.generator.width, Some(39));
+        // new lines fit the new width
+        for _ in 0..5 {
+            let line = test.line.expected().to_string();
+            type_str(&mut test, &(line + " "));
+            assert!(test.upcoming[0].expected().chars().count() <= 39);
+        }
+        let mut test = typing_test(&["-n", "50", "--width", "60"]);
+        test.update(Action::Resize(40, 10)).unwrap();
+        assert_eq!(test.generator.width, Some(60));
+    }
+
+    #[test]
+    fn end_choice_test() {
+        assert_eq!(EndChoice::from_action(Action::Enter), Some(EndChoice::Exit));
+        assert_eq!(EndChoice::from_action(Action::Quit), Some(EndChoice::Exit));
+        assert_eq!(
+            EndChoice::from_action(Action::Reset),
+            Some(EndChoice::Restart)
+        );
+        assert_eq!(EndChoice::from_action(Action::Type('a')), None);
+        assert_eq!(EndChoice::from_action(Action::Tick), None);
+    }
+
+    #[test]
+    fn end_screen_test() {
+        let enter = Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let tab = Event::Key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        let mut test = typing_test(&["-q", "-c", "one two"]);
+        test.set_screen(io::sink(), (80, 24));
+        // keys that do not choose anything are ignored
+        test.set_input(ScriptedInput::new([
+            Event::Resize(40, 10),
+            Event::Key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)),
+            enter,
+        ]));
+        assert_eq!(test.end_screen().unwrap(), EndChoice::Exit);
+        test.set_input(ScriptedInput::new([tab]));
+        assert_eq!(test.end_screen().unwrap(), EndChoice::Restart);
+        // restarting keeps finished results but starts a fresh test
+        test.set_input(ScriptedInput::from_text("one two"));
+        test.run_attempts().unwrap();
+        assert_eq!(test.results.len(), 1);
+        test.restart().unwrap();
+        assert!(test.running);
+        assert!(test.results.is_empty());
+        assert_eq!(test.earlier_results.len(), 1);
+        assert_eq!(test.line.typed_len(), 0);
+    }
+
+    #[test]
+    fn word_length_test() {
+        let mut test = typing_test(&["--min-word-length", "4", "--max-word-length", "6"]);
+        for _ in 0..20 {
+            let line = test.line.expected().to_string();
+            for word in line.split(' ') {
+                assert!((4..=6).contains(&word.chars().count()), "{:?}", word);
+            }
+            type_str(&mut test, &(line + " "));
+        }
+        let args = Args::from_iter(["typing_test", "--min-word-length", "100"]);
+        assert!(TypingTest::new(args).is_err());
+    }
+
+    #[test]
+    fn preview_test() {
+        let mut test = typing_test(&["--seed", "1", "--difficulty", "hard"]);
+        let preview = test.preview(PREVIEW_LINES);
+        assert_eq!(preview.len(), PREVIEW_LINES);
+        assert_eq!(preview[0], test.line.expected());
+        for word in preview.iter().flat_map(|line| line.split(' ')) {
+            assert!(word.chars().count() >= 8, "{:?}", word);
+        }
+        // short quotes have fewer lines
+        let mut test = typing_test(&["-q", "-c", "A short quote."]);
+        assert_eq!(test.preview(PREVIEW_LINES), ["A short quote."]);
+    }
+
+    #[test]
+    fn char_count_test() {
+        let mut test = typing_test(&["-q", "-c", "one two three"]);
+        type_str(&mut test, "onx tw");
+        assert_eq!(test.char_count(), 5);
+        assert_eq!(test.word_count(), 0);
+        let mut test = typing_test(&["-n", "50"]);
+        let line = test.line.expected().to_string();
+        type_str(&mut test, &(line.clone() + " x"));
+        // the space moving to the next line is counted
+        assert_eq!(test.char_count() as usize, line.chars().count() + 1);
+    }
+
+    #[test]
+    fn backspace_previous_line_test() {
+        let quote = (0..35).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let mut test = typing_test(&["-q", "-c", &quote]);
+        let first = test.line.expected().to_string();
+        let second = test.upcoming[0].expected().to_string();
+        // fix a mistake in the last word of the first line after moving on
+        type_str(&mut test, &first[..first.len() - 1]);
+        type_str(&mut test, "x ");
+        let (words, keystrokes) = (test.word_count(), test.keystrokes());
+        assert_eq!(test.line.expected(), second);
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.line.expected(), first);
+        assert_eq!(test.upcoming[0].expected(), second);
+        assert_eq!(test.previous_line.expected(), "");
+        assert_eq!(test.word_count(), words);
+        assert_eq!(test.keystrokes(), keystrokes);
+        assert_eq!(test._chars, 0);
+        test.update(Action::Backspace).unwrap();
+        type_str(&mut test, &first[first.len() - 1..]);
+        type_str(&mut test, " ");
+        assert_eq!(test.word_count(), words + 1);
+        // the third line comes back after the second
+        type_str(&mut test, &(second + " "));
+        assert_eq!(test.line.expected(), "20 21 22 23 24 25 26 27 28 29");
+        assert_eq!(test.upcoming[0].expected(), "30 31 32 33 34");
+        // the start of the first line and generated lines stay put
+        let mut test = typing_test(&["-q", "-c", &quote]);
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.line.expected(), first);
+        let mut test = typing_test(&["-n", "50"]);
+        let line = test.line.expected().to_string();
+        type_str(&mut test, &(line.clone() + " "));
+        test.update(Action::Backspace).unwrap();
+        assert_ne!(test.line.expected(), line);
+    }
+
+    #[test]
+    fn reaction_time_test() {
+        let shown = Instant::now();
+        let first_key = shown + Duration::from_millis(420);
+        assert_eq!(reaction_time(shown, first_key), Duration::from_millis(420));
+        assert_eq!(reaction_time(first_key, shown), Duration::ZERO);
+        let mut test = typing_test(&["--precision", "2"]);
+        test.shown = Some(Instant::now() - Duration::from_millis(420));
+        test.type_char('a');
+        assert!(test.reaction.unwrap() >= Duration::from_millis(420));
+        assert!(test
+            .summary_lines()
+            .iter()
+            .any(|line| line.starts_with("Reaction: 0.4")));
+        test.reset().unwrap();
+        assert_eq!(test.reaction, None);
+    }
+
+    #[test]
+    fn blind_test() {
+        let mut test = typing_test(&["--blind"]);
+        let expected: Vec<char> = test.line.expected().chars().collect();
+        test.type_char(expected[0]);
+        test.type_char('#');
+        assert_eq!(test.keystrokes(), (2, 1));
+        assert_eq!(test.accuracy(), Some(50.0));
+        let mut drawn = Vec::new();
+        let highlight = test.highlight_errors;
+        test.line
+            .draw(&mut drawn, 80, &test.theme, highlight, None)
+            .unwrap();
+        let error = crossterm::style::SetForegroundColor(test.theme.error).to_string();
+        assert!(!String::from_utf8(drawn).unwrap().contains(&error));
+        assert!(test
+            .summary_lines()
+            .iter()
+            .any(|line| line.starts_with("Accuracy: 50")));
+    }
+
+    #[test]
+    fn missed_keys_test() {
+        let mut test = typing_test(&["-q", "-c", "abc abd"]);
+        assert_eq!(test.missed_key_line(), None);
+        // errors are counted for the expected character, not the typed one
+        type_str(&mut test, "xbx");
+        test.update(Action::BackspaceWord).unwrap();
+        type_str(&mut test, "abx ab");
+        test.type_char('q');
+        assert_eq!(test.errors, HashMap::from([('a', 1), ('c', 2), ('d', 1)]));
+        assert_eq!(
+            test.missed_key_line(),
+            Some("Most missed keys: c (2), a (1), d (1)".into())
+        );
+    }
+
+    #[test]
+    fn code_test() {
+        let mut test = typing_test(&["--code", "-n", "100"]);
+        for _ in 0..5 {
+            let expected = test.line.expected().to_string();
+            let code = expected.trim_start();
+            let words = code.split(' ').count() as u32;
+            let before = test.word_count();
+            type_str(&mut test, &format!("{} ", code));
+            assert_eq!(test.word_count(), before + words, "{:?}", expected);
+        }
+    }
+
+    #[test]
+    fn personal_best_test() {
+        let mut test = typing_test(&["-n", "10", "--precision", "0"]);
+        let result = |words| TestResult {
+            mode: "10 words".into(),
+            words,
+            raw_words: words,
+            elapsed: 60.0,
+            keystrokes: 100,
+            errors: 0,
+        };
+        test.results.push(result(50));
+        assert_eq!(test.personal_best_line(), None);
+        test.personal_best = Some(60.0);
+        assert_eq!(
+            test.personal_best_line(),
+            Some("Personal best: 60 wpm".into())
+        );
+        test.results.push(result(70));
+        assert_eq!(
+            test.personal_best_line(),
+            Some("New personal best! 70 wpm beats 60 wpm".into())
+        );
+        // the new record is the one to beat after restarting
+        test.restart().unwrap();
+        assert_eq!(test.personal_best, Some(70.0));
+    }
+
+    #[test]
+    fn hardcore_test() {
+        let mut test = typing_test(&["--hardcore"]);
+        let expected: Vec<char> = test.line.expected().chars().collect();
+        test.update(Action::Type(expected[0])).unwrap();
+        assert_eq!(test.check_stop(), None);
+        // backspacing is not a mistake
+        test.update(Action::Backspace).unwrap();
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.check_stop(), None);
+        test.update(Action::Type(expected[0])).unwrap();
+        test.update(Action::Type('#')).unwrap();
+        assert_eq!(test.check_stop(), Some("made a mistake"));
+        // without the flag mistakes do not end the test
+        let mut test = typing_test(&[]);
+        test.update(Action::Type('#')).unwrap();
+        assert_eq!(test.check_stop(), None);
+    }
+
+    #[test]
+    fn must_be_clean_test() {
+        let mut test = typing_test(&["-n", "3", "--must-be-clean"]);
+        let words: Vec<String> = test.line.expected().split(' ').map(String::from).collect();
+        type_str(
+            &mut test,
+            &format!("{} {} {}", words[0], words[1], words[2]),
+        );
+        assert_eq!(test.check_stop(), Some("reached word count"));
+        type_str(&mut test, " #");
+        assert_eq!(test.word_count(), 3);
+        assert_eq!(test.check_stop(), None);
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.check_stop(), Some("reached word count"));
+        // a mistyped earlier word still has to be fixed once the count is reached
+        let mut test = typing_test(&["-n", "2", "--must-be-clean"]);
+        let words: Vec<String> = test.line.expected().split(' ').map(String::from).collect();
+        type_str(
+            &mut test,
+            &format!(
+                "{} {} {} {} ",
+                "#".repeat(words[0].len()),
+                words[1],
+                words[2],
+                words[3]
+            ),
+        );
+        assert_eq!(test.word_count(), 3);
+        assert_eq!(test.check_stop(), None);
+        // without the flag trailing mistakes do not keep the test going
+        let mut test = typing_test(&["-n", "3"]);
+        let words: Vec<String> = test.line.expected().split(' ').map(String::from).collect();
+        type_str(
+            &mut test,
+            &format!("{} {} {} #", words[0], words[1], words[2]),
+        );
+        assert_eq!(test.check_stop(), Some("reached word count"));
+    }
+
+    #[test]
+    fn delay_start_test() {
+        let mut test = typing_test(&["--delay-start", "500"]);
+        let now = Instant::now();
+        test.shown = Some(now);
+        assert!(test.in_grace_period(now + Duration::from_millis(499)));
+        assert!(!test.in_grace_period(now + Duration::from_millis(500)));
+        let first = test.line.expected().chars().next().unwrap();
+        assert!(!test.type_char(first));
+        assert!(test.stopwatch.is_none());
+        assert_eq!(test.line.typed_len(), 0);
+        test.shown = Some(now - Duration::from_secs(1));
+        assert!(test.type_char(first));
+        assert!(test.stopwatch.is_some());
+        // the grace period is only before the test starts
+        test.shown = Some(Instant::now());
+        assert!(test.type_char('x'));
+        assert!(!typing_test(&[]).in_grace_period(now));
+    }
+
+    #[test]
+    fn confirm_restart_test() {
+        let mut test = typing_test(&["--confirm-restart"]);
+        let now = Instant::now();
+        // nothing is lost before the test starts so one Tab is enough
+        assert!(test.confirm_reset(now));
+        let first = test.line.expected().chars().next().unwrap();
+        test.update(Action::Type(first)).unwrap();
+        assert!(!test.confirm_reset(now));
+        assert!(test.restart_pending(now + Duration::from_millis(1499)));
+        assert!(test.confirm_reset(now + Duration::from_millis(1499)));
+        assert_eq!(test.last_tab, None);
+        // a second Tab after the window only starts a new window
+        assert!(!test.confirm_reset(now));
+        assert!(!test.restart_pending(now + RESTART_WINDOW));
+        assert!(!test.confirm_reset(now + RESTART_WINDOW));
+        assert!(test.confirm_reset(now + RESTART_WINDOW + Duration::from_millis(100)));
+        // a double tap through update restarts the test
+        test.update(Action::Reset).unwrap();
+        assert!(test.stopwatch.is_some());
+        test.update(Action::Reset).unwrap();
+        assert!(test.stopwatch.is_none());
+        assert_eq!(test.last_tab, None);
+        // without the flag every Tab restarts
+        let mut test = typing_test(&[]);
+        test.update(Action::Type(first)).unwrap();
+        assert!(test.confirm_reset(now));
+        assert!(test.confirm_reset(now));
+    }
+
+    #[test]
+    fn restart_prompt_color_test() {
+        let mut test = typing_test(&["--confirm-restart"]);
+        let color = Color::Rgb { r: 1, g: 2, b: 3 };
+        test.theme.labels = Some(color);
+        let first = test.line.expected().chars().next().unwrap();
+        test.update(Action::Type(first)).unwrap();
+        test.update(Action::Reset).unwrap();
+        let screen = SharedScreen::default();
+        test.set_screen(screen.clone(), (80, 24));
+        test.draw_score().unwrap();
+        let drawn = String::from_utf8(screen.0.borrow().clone()).unwrap();
+        let prompt = "Press Tab again to restart".with(color).bold();
+        assert!(drawn.contains(&prompt.to_string()), "{:?}", drawn);
+    }
+
+    #[test]
+    fn describe_stats_test() {
+        let mut stats = Stats {
+            tests: 0,
+            average: stats::Average {
+                wpm: None,
+                accuracy: None,
+            },
+            best: None,
+            trend: None,
+        };
+        assert_eq!(describe_stats(&stats, 2), ["There are no results yet"]);
+        stats.tests = 3;
+        stats.average.wpm = Some(60.5);
+        stats.average.accuracy = Some(95.0);
+        stats.best = Some(72.25);
+        assert_eq!(
+            describe_stats(&stats, 1),
+            [
+                "Tests: 3",
+                "Average wpm: 60.5",
+                "Best wpm: 72.2",
+                "Average accuracy: 95.0%"
+            ]
+        );
+        for (trend, expected) in [
+            (4.0, "Trend: improving (+4 wpm over the last 3 tests)"),
+            (-2.0, "Trend: declining (-2 wpm over the last 3 tests)"),
+            (0.5, "Trend: steady (+0 wpm over the last 3 tests)"),
+        ] {
+            stats.trend = Some(trend);
+            assert_eq!(describe_stats(&stats, 0).last().unwrap(), expected);
+        }
+        stats.tests = 50;
+        let lines = describe_stats(&stats, 0);
+        assert!(lines.last().unwrap().ends_with("over the last 10 tests)"));
+    }
+
+    #[test]
+    fn keystroke_lines_test() {
+        let mut test = typing_test(&["-q", "-c", "the quick brown fox"]);
+        assert!(test.keystroke_lines().is_empty());
+        // one mistake left in, one fixed with backspace, and two fixed with ctrl+backspace
+        type_str(&mut test, "tge qa");
+        test.update(Action::Backspace).unwrap();
+        type_str(&mut test, "uick bxx");
+        test.update(Action::BackspaceWord).unwrap();
+        type_str(&mut test, "brown fox");
+        assert_eq!(test.keystrokes(), (23, 4));
+        assert_eq!(test.fixed_keystrokes(), 3);
+        assert_eq!(
+            test.keystroke_lines(),
+            [
+                "Keystrokes: Total  Correct  Incorrect  Fixed",
+                "               23       19          4      3"
+            ]
+        );
+        test.reset().unwrap();
+        assert_eq!(test.fixed_keystrokes(), 0);
+    }
+
+    #[test]
+    fn keystroke_fixed_previous_line_test() {
+        let quote = (0..15).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let mut test = typing_test(&["-q", "-c", &quote]);
+        let first = test.line.expected().to_string();
+        type_str(&mut test, "x");
+        test.update(Action::Backspace).unwrap();
+        type_str(&mut test, &first[..first.len() - 1]);
+        type_str(&mut test, "x ");
+        assert_eq!(test.fixed_keystrokes(), 1);
+        // going back to the previous line keeps its fixed keystrokes
+        test.update(Action::Backspace).unwrap();
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.fixed_keystrokes(), 2);
+    }
+
+    #[test]
+    fn validate_limits_test() {
+        assert_eq!(validate_limits(None, None), Ok(()));
+        assert_eq!(validate_limits(Some(1), Some(1)), Ok(()));
+        assert_eq!(validate_limits(Some(50), None), Ok(()));
+        assert_eq!(validate_limits(None, Some(60)), Ok(()));
+        let e = validate_limits(Some(0), None).unwrap_err();
+        assert!(e.contains("number of words"), "{}", e);
+        let e = validate_limits(None, Some(0)).unwrap_err();
+        assert!(e.contains("time limit"), "{}", e);
+        assert!(validate_limits(Some(10), Some(0)).is_err());
+    }
+
+    #[test]
+    fn show_fetch_status_test() {
+        let shown = |status| {
+            let mut out = Vec::new();
+            show_fetch_status(&mut out, status).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let clear = format!("\r{}", terminal::Clear(terminal::ClearType::CurrentLine));
+        assert_eq!(
+            shown(FetchStatus::Fetching),
+            format!("{clear}Fetching quote...")
+        );
+        assert_eq!(shown(FetchStatus::Fetched), clear);
+        let e = quote::QuoteError::Network("url".into());
+        let failed = shown(FetchStatus::Failed(&e));
+        assert!(failed.starts_with(&clear));
+        assert!(failed.ends_with("Using a bundled quote instead.\r\n"));
+    }
+
+    #[test]
+    fn set_fetch_status_test() {
+        let mut test = typing_test(&["-q", "--offline"]);
+        let screen = SharedScreen::default();
+        test.set_screen(screen.clone(), (120, 24));
+        test.set_fetch_status(FetchStatus::Fetching);
+        let drawn = String::from_utf8(screen.0.borrow().clone()).unwrap();
+        // the status is part of the redrawn score line instead of printed over the test
+        let score = drawn.lines().next().unwrap();
+        assert!(score.contains("fetching..."), "{:?}", drawn);
+        let e = quote::QuoteError::Network("url".into());
+        test.set_fetch_status(FetchStatus::Failed(&e));
+        assert_eq!(test.fetch_status, Some("using a bundled quote"));
+        test.set_fetch_status(FetchStatus::Fetched);
+        assert_eq!(test.fetch_status, None);
+    }
+
+    #[test]
+    fn describe_coverage_test() {
+        let mut coverage = Coverage::default();
+        coverage.record("ferris crab");
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "ferris crab crab rust cargo").unwrap();
+        let path = file.path().to_str().unwrap();
+        // the word file is used instead of the english word list
+        let args = parse_args(["typing_test", "--word-file", path]).unwrap();
+        let words = word_list(&args).unwrap();
+        assert_eq!(
+            describe_coverage(&coverage, &words),
+            "Coverage: 50% of word list seen"
+        );
+        let args =
+            parse_args(["typing_test", "--word-file", path, "--max-word-length", "4"]).unwrap();
+        assert_eq!(
+            describe_coverage(&coverage, &word_list(&args).unwrap()),
+            "Coverage: 50% of word list seen"
+        );
+    }
+
+    #[test]
+    fn max_line_width_test() {
+        let quote = (0..40).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let test = typing_test(&["-q", "-c", &quote, "--max-line-width", "20"]);
+        assert_eq!(test.line.expected(), "0 1 2 3 4 5 6 7 8 9");
+        assert_eq!(test.upcoming[0].expected(), "10 11 12 13 14 15 16");
+        let test = typing_test(&["-n", "50", "--max-line-width", "30"]);
+        assert_eq!(test.generator.width, Some(30));
+        assert!(test.line.expected().chars().count() <= 30);
+        // quotes keep 10 words per line without a width
+        let test = typing_test(&["-q", "-c", &quote]);
+        assert_eq!(test.upcoming[0].expected(), "10 11 12 13 14 15 16 17 18 19");
+    }
+
+    #[test]
+    fn words_file_weighted_test() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "ferris\t9\ncrab\t1").unwrap();
+        let path = file.path().to_str().unwrap();
+        let args = parse_args([
+            "typing_test",
+            "--words-file-weighted",
+            path,
+            "--max-word-length",
+            "4",
+        ])
+        .unwrap();
+        let test = TypingTest::new(args).unwrap();
+        assert_eq!(test.generator.words, ["crab"]);
+        assert!(
+            matches!(&test.generator.weighting, Weighting::ByWord(weights) if weights.len() == 2)
+        );
+        assert!(test.line.expected().split(' ').all(|word| word == "crab"));
+    }
+
+    #[test]
+    fn transcript_lines_test() {
+        let quote = (0..15).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let mut test = typing_test(&["-q", "-c", &quote, "--transcript"]);
+        assert!(test.show_transcript);
+        assert!(test.transcript_lines().is_empty());
+        type_str(&mut test, "0 x 2 3 4 5 6 7 8 9 10 11 1");
+        let error = test.theme.error;
+        let lines = test.transcript_lines();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(&format!("{} {}", "0".dim(), "1".with(error))));
+        assert_eq!(
+            lines[1],
+            format!("{} {} {}", "10".dim(), "11".dim(), "12".with(error))
+        );
+        // moving back to the previous line takes it out of the transcript until it is finished again
+        test.update(Action::BackspaceWord).unwrap();
+        test.update(Action::BackspaceWord).unwrap();
+        test.update(Action::BackspaceWord).unwrap();
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.typed_lines.len(), 0);
+        assert_eq!(test.transcript_lines().len(), 1);
+        test.reset().unwrap();
+        assert!(test.transcript_lines().is_empty());
+    }
+
+    #[test]
+    fn refill_test() {
+        let mut remaining = String::new();
+        refill(&mut remaining, || "A new quote.".into());
+        assert_eq!(remaining, "A new quote.");
+        refill(&mut remaining, || unreachable!());
+        assert_eq!(remaining, "A new quote.");
+    }
+
+    #[test]
+    fn endless_quotes_test() {
+        let mut test = typing_test(&["-n", "200", "--endless-quotes", "--offline"]);
+        assert_eq!(test.test_mode.to_string(), "200 words");
+        // more lines keep coming after the first quote runs out
+        for _ in 0..20 {
+            let line = test.line.expected().to_string();
+            assert!(!line.is_empty());
+            type_str(&mut test, &(line + " "));
+        }
+        assert_eq!(test.check_stop(), None);
+        assert!(test.word_count() > 20);
+    }
+
+    #[test]
+    fn endless_quotes_prefetch_test() {
+        use std::net::TcpListener;
+        let mut test = typing_test(&["-n", "200", "--endless-quotes", "--offline"]);
+        assert!(test.next_quote.is_some());
+        test.set_screen(io::sink(), (80, 24));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        test.quotes = QuoteSource {
+            url: format!("http://{}", listener.local_addr().unwrap()),
+            timeout: Duration::from_secs(5),
+            offline: false,
+        };
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            // the quote is slow to arrive
+            std::thread::sleep(Duration::from_millis(300));
+            let response = "{\"content\":\"Slow quote.\"}";
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            );
+        });
+        test.next_quote = Some(test.quotes.prefetch());
+        test.quotes.offline = true;
+        test.stopwatch = Some(Stopwatch::start());
+        test.endless_quotes = Some(String::new());
+        let start = Instant::now();
+        assert_eq!(test.new_line().expected(), "Slow quote.");
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        // waiting on the quote does not count as time spent typing
+        let stopwatch = test.stopwatch.unwrap();
+        assert!(!stopwatch.paused());
+        assert!(stopwatch.elapsed() < Duration::from_millis(300));
+        // the quote after it is already on its way
+        assert!(test.next_quote.is_some());
+    }
+
+    #[test]
+    fn zen_test() {
+        let mut test = typing_test(&["--zen"]);
+        assert!(matches!(test.test_mode, TestMode::Zen));
+        assert_eq!(test.remaining(), None);
+        for _ in 0..5 {
+            let line = test.line.expected().to_string();
+            type_str(&mut test, &(line + " "));
+        }
+        assert!(test.word_count() > 0);
+        assert_eq!(stop::check(&test.stop_conditions, &test.progress()), None);
+        test.update(Action::Quit).unwrap();
+        assert!(test.show_final_score);
+    }
+
+    #[test]
+    fn remaining_quote_lines_test() {
+        let quote = (0..25).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let mut test = typing_test(&["-q", "-c", &quote]);
+        let line = test.line.expected().to_string();
+        type_str(&mut test, &(line + " "));
+        let (_, percent) = test.remaining().unwrap();
+        let expected = test._chars * 100 / quote.len();
+        assert_eq!(percent, format!("{}%", expected));
+    }
+
+    /// A screen that can still be read after it is given to a test
+    #[derive(Clone, Default)]
+    struct SharedScreen(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedScreen {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn interrupt_test() {
+        let mut test = typing_test(&["--zen", "--caret-char", "|"]);
+        type_str(&mut test, "some");
+        assert!(test.update(Action::Interrupt).unwrap());
+        assert!(!test.running);
+        // unlike quitting a zen test there is no summary
+        assert!(!test.show_final_score);
+        let screen = SharedScreen::default();
+        test.set_screen(screen.clone(), (80, 24));
+        test.restore_terminal().unwrap();
+        let drawn = String::from_utf8(screen.0.borrow().clone()).unwrap();
+        assert!(drawn.ends_with(&cursor::Show.to_string()), "{:?}", drawn);
+        // ctrl+c also stops a scripted run before it finishes
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+        let mut test = typing_test(&["-n", "50"]);
+        let key = |ch, modifiers| Event::Key(KeyEvent::new(KeyCode::Char(ch), modifiers));
+        let mut events: Vec<_> = "the"
+            .chars()
+            .map(|ch| key(ch, KeyModifiers::NONE))
+            .collect();
+        events.push(key('c', KeyModifiers::CONTROL));
+        events.extend("more".chars().map(|ch| key(ch, KeyModifiers::NONE)));
+        test.set_input(ScriptedInput::new(events));
+        test.set_screen(io::sink(), (80, 24));
+        test.run_with_end_screen().unwrap();
+        assert_eq!(test.keystrokes().0, 3);
+        // the abandoned test is not saved to the history or csv
+        assert!(test.results().is_empty());
+        assert!(test.earlier_results.is_empty());
+    }
+
+    #[test]
+    fn update_test() {
+        let mut test = typing_test(&[]);
+        assert!(!test.update(Action::Tick).unwrap());
+        assert!(test.update(Action::Type('a')).unwrap());
+        assert!(test.update(Action::Type('b')).unwrap());
+        assert_eq!(test.line.index(), 2);
+        assert!(test.update(Action::Tick).unwrap());
+        assert!(test.update(Action::Backspace).unwrap());
+        assert_eq!(test.line.index(), 1);
+        assert!(test.update(Action::Reset).unwrap());
+        assert_eq!(test.line.index(), 0);
+        assert!(test.stopwatch.is_none());
+        assert!(test.update(Action::Quit).unwrap());
+        assert!(!test.running);
+        assert!(!test.show_final_score);
+    }
+
+    #[test]
+    fn update_next_line_test() {
+        let mut test = typing_test(&[]);
+        let expected = test.line.expected().to_string();
+        let next = test.upcoming[0].expected().to_string();
+        for ch in expected.chars() {
+            test.update(Action::Type(ch)).unwrap();
+        }
+        assert_eq!(test.word_count(), expected.split(' ').count() as u32);
+        test.update(Action::Type(' ')).unwrap();
+        assert_eq!(test.previous_line.expected(), expected);
+        assert_eq!(test.line.expected(), next);
+        assert_eq!(test.line.index(), 0);
+    }
+
+    #[test]
+    fn lookahead_test() {
+        let mut test = typing_test(&["--lookahead", "3"]);
+        assert_eq!(test.upcoming.len(), 3);
+        let lines: Vec<String> = test
+            .upcoming
+            .iter()
+            .map(|line| line.expected().into())
+            .collect();
+        let expected = test.line.expected().to_string();
+        type_str(&mut test, &(expected.clone() + " "));
+        // every upcoming line moves up one and a new one is added at the end
+        assert_eq!(test.previous_line.expected(), expected);
+        assert_eq!(test.line.expected(), lines[0]);
+        assert_eq!(test.upcoming[0].expected(), lines[1]);
+        assert_eq!(test.upcoming[1].expected(), lines[2]);
+        assert_eq!(test.upcoming.len(), 3);
+        test.update(Action::Skip).unwrap();
+        assert_eq!(test.line.expected(), lines[1]);
+        assert_eq!(test.upcoming.len(), 3);
+        test.reset().unwrap();
+        assert_eq!(test.upcoming.len(), 3);
+        let outcome = parse_args(["typing_test", "--lookahead", "0"]);
+        assert_eq!(outcome.unwrap_err().exit_code(), 2);
+    }
+
+    #[test]
+    fn no_next_line_test() {
+        let mut test = typing_test(&["--no-next-line"]);
+        test.set_screen(io::sink(), (80, 24));
+        assert_eq!(test.shown_upcoming(), 0);
+        assert_eq!(test.upcoming.len(), 1);
+        let next = test.upcoming[0].expected().to_string();
+        assert!(!next.is_empty());
+        let expected = test.line.expected().to_string();
+        type_str(&mut test, &(expected + " "));
+        test.render().unwrap();
+        assert_eq!(test.line.expected(), next);
+        assert_eq!(test.upcoming.len(), 1);
+        assert_eq!(typing_test(&[]).shown_upcoming(), 1);
+    }
+
+    #[test]
+    fn center_test() {
+        let mut test = typing_test(&["--center", "-q", "-c", "one two three"]);
+        assert_eq!(padding(&test.line, 80, test.center), 33);
+        assert_eq!(test.cursor_column(80), 33);
+        type_str(&mut test, "one ");
+        assert_eq!(test.cursor_column(80), 37);
+        assert_eq!(padding(&Line::EMPTY, 80, test.center), 40);
+        let test = typing_test(&["-q", "-c", "one two three"]);
+        assert_eq!(padding(&test.line, 80, test.center), 0);
+        assert_eq!(test.cursor_column(80), 0);
+    }
+
+    #[test]
+    fn quiet_test() {
+        assert_eq!(typing_test(&[]).line_row(), 2);
+        let mut test = typing_test(&["--quiet"]);
+        assert_eq!(test.line_row(), 1);
+        test.set_screen(io::sink(), (80, 24));
+        test.render().unwrap();
+        test.stopwatch = Some(Stopwatch::start());
+        assert!(!test.summary_lines().is_empty());
+    }
+
+    #[test]
+    fn lookahead_quote_test() {
+        let quote = (0..45).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let mut test = typing_test(&["-q", "-c", &quote, "--lookahead", "2"]);
+        let first = test.line.expected().to_string();
+        type_str(&mut test, &(first.clone() + " "));
+        assert_eq!(test.upcoming[1].expected(), "30 31 32 33 34 35 36 37 38 39");
+        // the last upcoming line goes back into the quote when backspacing
+        test.update(Action::Backspace).unwrap();
+        assert_eq!(test.line.expected(), first);
+        assert_eq!(test.upcoming[1].expected(), "20 21 22 23 24 25 26 27 28 29");
+        assert_eq!(test.remaining(), Some(("Complete", "15%".into())));
+        type_str(&mut test, " ");
+        assert_eq!(test.upcoming[1].expected(), "30 31 32 33 34 35 36 37 38 39");
+    }
+
+    #[test]
+    fn accuracy_test() {
+        let mut test = typing_test(&[]);
+        assert_eq!(test.accuracy(), None);
+        let expected: Vec<char> = test.line.expected().chars().collect();
+        test.type_char(expected[0]);
+        test.type_char('#');
+        test.type_char(expected[2]);
+        test.type_char(expected[3]);
+        assert_eq!(test.keystrokes(), (4, 1));
+        assert_eq!(test.accuracy(), Some(75.0));
+    }
+
+    #[test]
+    fn accuracy_across_lines_test() {
+        let quote = (0..25).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let mut test = typing_test(&["-q", "-c", &quote]);
+        // one mistake fixed on the first line
+        test.update(Action::Type('x')).unwrap();
+        test.update(Action::Backspace).unwrap();
+        type_str(&mut test, "0 1 2 3 4 5 6 7 8 9 ");
+        assert_eq!(test.keystrokes(), (20, 1));
+        // mistakes on finished lines still count once the line is gone
+        type_str(&mut test, "1#");
+        test.update(Action::Backspace).unwrap();
+        type_str(&mut test, "0 11 12 13 14 15 16 17 18 19 ");
+        assert_eq!(test.keystrokes(), (50, 2));
+        assert_eq!(test.accuracy(), Some(96.0));
+        // and so do mistakes on the current line
+        type_str(&mut test, "2#");
+        assert_eq!(test.keystrokes(), (52, 3));
+        assert_eq!(test.result().unwrap().accuracy(), test.accuracy());
+    }
+
+    #[test]
+    fn parse_args_quote_file_test() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "A quote\nin  a file").unwrap();
+        let path = file.path().to_str().unwrap();
+        let args = parse_args(["typing_test", "--quote-file", path]).unwrap();
+        assert_eq!(args.custom_quote, Some("A quote in a file".into()));
+        assert!(args.quote);
+        let outcome = parse_args(["typing_test", "--quote-file", "does/not/exist"]);
+        assert_eq!(outcome.unwrap_err().exit_code(), 2);
+        let outcome = parse_args(["typing_test", "--quote-file", path, "-c", "quote"]);
+        assert_eq!(outcome.unwrap_err().exit_code(), 2);
+    }
+
+    #[test]
+    fn parse_args_custom_quote_test() {
+        let args = parse_args(["typing_test", "-c", "\tA  messy\nquote "]).unwrap();
+        assert_eq!(args.custom_quote, Some("A messy quote".into()));
+        let mut test = TypingTest::new(args).unwrap();
+        assert_eq!(test.line.expected(), "A messy quote");
+        type_str(&mut test, "A messy quote");
+        assert_eq!(test.word_count(), 3);
+        let outcome = parse_args(["typing_test", "-c", " \n\t "]);
+        assert_eq!(outcome.unwrap_err().exit_code(), 2);
+    }
+
+    #[test]
+    fn validate_modes_test() {
+        let mut args = parse_args(["typing_test", "--code", "--menu"]).unwrap();
+        assert!(validate_modes(&args).is_ok());
+        // choosing quote or zen from the menu conflicts with --code
+        args.quote = true;
+        assert!(validate_modes(&args).is_err());
+        args.quote = false;
+        args.zen = true;
+        assert!(validate_modes(&args).is_err());
+    }
+
+    #[test]
+    fn replay_not_saved_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("results.csv");
+        let mut test = typing_test(&["--replay", "keys.jsonl", "--csv", csv.to_str().unwrap()]);
+        assert!(test.replaying);
+        test.results.push(TestResult {
+            mode: "30 words".into(),
+            words: 30,
+            raw_words: 30,
+            elapsed: 60.0,
+            keystrokes: 150,
+            errors: 0,
+        });
+        test.save_results();
+        assert!(!csv.exists());
+    }
+
+    #[test]
+    fn parse_args_replay_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let words_path = dir.path().join("words.jsonl");
+        let words_file = words_path.to_str().unwrap();
+        let recorded =
+            TypingTest::new(parse_args(["typing_test", "--record", words_file]).unwrap()).unwrap();
+        let args = parse_args(["typing_test", "--replay", words_file]).unwrap();
+        assert!(args.seed.is_some());
+        let replayed = TypingTest::new(args).unwrap();
+        assert_eq!(replayed.line.expected(), recorded.line.expected());
+        let quote_path = dir.path().join("quote.jsonl");
+        let quote_file = quote_path.to_str().unwrap();
+        TypingTest::new(
+            parse_args(["typing_test", "--record", quote_file, "-c", "a quote"]).unwrap(),
+        )
+        .unwrap();
+        let args = parse_args(["typing_test", "--replay", quote_file]).unwrap();
+        assert_eq!(args.custom_quote, Some("a quote".into()));
+        assert!(args.quote);
+        let outcome = parse_args(["typing_test", "--replay", "does/not/exist"]);
+        assert_eq!(outcome.unwrap_err().exit_code(), 2);
+    }
+
+    #[test]
+    fn parse_args_replay_speed_test() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "{{\"ms\":0,\"key\":\"a\"}}").unwrap();
+        let path = file.path().to_str().unwrap();
+        for speed in ["0", "-1", "NaN"] {
+            let speed = format!("--replay-speed={}", speed);
+            match parse_args(["typing_test", "--replay", path, &speed]) {
+                Err(Outcome::InvalidArgs(e)) => assert!(e.contains("replay speed"), "{}", e),
+                _ => panic!("{} was accepted", speed),
+            }
+        }
+        let args = parse_args(["typing_test", "--replay", path, "--replay-speed", "2"]).unwrap();
+        assert_eq!(args.replay_speed, 2.0);
+        assert_eq!(args.replay_keys.len(), 1);
+    }
+
+    #[test]
+    fn outcome_exit_code_test() {
+        assert_eq!(Outcome::Success.exit_code(), 0);
+        assert_eq!(Outcome::RuntimeError(String::new()).exit_code(), 1);
+        assert_eq!(Outcome::InvalidArgs(String::new()).exit_code(), 2);
+        assert_eq!(Outcome::TargetMissed.exit_code(), 3);
+    }
+
+    #[test]
+    fn missed_target_test() {
+        let result = |words| TestResult {
+            mode: "30 words".into(),
+            words,
+            raw_words: words,
+            elapsed: 60.0,
+            keystrokes: 100,
+            errors: 0,
+        };
+        let mut test = typing_test(&["--target-wpm", "50"]);
+        // nothing finished so there is nothing to miss
+        assert!(!test.missed_target());
+        test.results.push(result(40));
+        assert!(test.missed_target());
+        test.results.push(result(70));
+        assert!(!test.missed_target());
+        let mut test = typing_test(&[]);
+        test.results.push(result(10));
+        assert!(!test.missed_target());
+    }
+}
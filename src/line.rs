@@ -2,18 +2,96 @@
 //! as well as generating new lines
 use crossterm::{
     cursor, queue,
-    style::{Color, PrintStyledContent, Stylize},
+    style::{Color, PrintStyledContent, StyledContent, Stylize},
 };
-use std::io;
+use rand::{distributions::WeightedIndex, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 const COMPLETED: Color = gray(255);
 const UNCOMPLETED: Color = gray(100);
 const ERROR: Color = Color::Rgb { r: 230, g: 0, b: 0 };
+/// The background of the column the pacer is on
+const PACE: Color = gray(60);
 const LINE_LEN: usize = 10;
+/// The fewest words a line of a quote can be cut down to so it ends with a sentence
+const MIN_SENTENCE_WORDS: usize = 4;
+/// Drawn in place of characters that do not fit on the screen
+const OVERFLOW: char = '…';
+/// The chance that a learned word gets replaced when it is picked
+const LEARNED_SKIP_CHANCE: f32 = 0.75;
+/// The most times a word is drawn again to find one that has not been picked for the line
+const MAX_REDRAWS: usize = 50;
+/// Punctuation marks that can follow words
+const PUNCTUATION: [char; 3] = [',', '.', '?'];
+/// The chance of a word being followed by punctuation
+const PUNCTUATION_CHANCE: f32 = 0.2;
+/// The chance of a word being replaced by a number
+const NUMBER_CHANCE: f64 = 0.15;
+/// The largest number that can replace a word
+const MAX_NUMBER: u32 = 9999;
 
 /// ALL of the words possible
 /// taken from <https://github.com/monkeytypegame/monkeytype/blob/master/frontend/static/languages/english.json>
-const WORDS: &[&str] = include!("words.txt");
+pub const WORDS: &[&str] = include!("words.txt");
+
+/// Lines of code used to practice typing symbols, some of them indented
+const CODE: &[&str] = &[
+    "fn main() {",
+    "    let mut total = 0;",
+    "    for item in items.iter() {",
+    "        total += item.price * item.count;",
+    "    }",
+    "    println!(\"{}\", total);",
+    "}",
+    "const userName = getUserName(request);",
+    "if (count > 0 && !isDone) {",
+    "    return items.map((item) => item.id);",
+    "} else {",
+    "    throw new Error(\"empty list\");",
+    "let result: Vec<u32> = values.into_iter().collect();",
+    "match event {",
+    "    Event::Key(key) => handleKey(key),",
+    "    _ => {}",
+    "def parse_args(argv):",
+    "    return [arg.strip() for arg in argv if arg]",
+    "while (index < buffer.length) {",
+    "    index++;",
+    "int* pointer = &values[0];",
+    "export function isValid(input: string): boolean {",
+    "    return input.length >= 8;",
+    "impl Display for Point {",
+    "    fn fmt(&self, f: &mut Formatter) -> Result {",
+    "        write!(f, \"({}, {})\", self.x, self.y)",
+    "struct Config { maxRetries: u8, timeoutMs: u64 }",
+    "const isEven = (n) => n % 2 === 0;",
+    "x = {\"key\": [1, 2, 3], \"other\": None}",
+    "let maybeValue = cache.get(&key)?.clone();",
+];
+
+/// The bundled word lists and the languages they are in
+pub const LANGUAGES: &[(&str, &[&str])] = &[
+    ("english", WORDS),
+    ("german", include!("languages/german.txt")),
+    ("spanish", include!("languages/spanish.txt")),
+];
+
+/// Get the bundled word list of a language
+pub fn language_words(language: &str) -> Result<&'static [&'static str], String> {
+    LANGUAGES
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map(|(_, words)| *words)
+        .ok_or_else(|| {
+            format!("Unknown language \"{language}\". Use --list-languages to see the available languages.")
+        })
+}
 
 /// Return a color where the r, g, and b values are set to x
 /// Effectively a grayscale color
@@ -21,6 +99,151 @@ const fn gray(x: u8) -> Color {
     Color::Rgb { r: x, g: x, b: x }
 }
 
+/// The colors lines are drawn with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// Color of correctly typed characters
+    pub completed: Color,
+    /// Color of characters that have not been typed yet
+    pub uncompleted: Color,
+    /// Color of incorrectly typed characters
+    pub error: Color,
+    /// Color of the score labels, or each label's own color if not set
+    pub labels: Option<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            completed: COMPLETED,
+            uncompleted: UNCOMPLETED,
+            error: ERROR,
+            labels: None,
+        }
+    }
+}
+
+impl Theme {
+    /// Get a built-in theme by name
+    pub fn named(name: &str) -> Result<Self, String> {
+        THEMES
+            .iter()
+            .find(|(theme, _)| *theme == name)
+            .map(|(_, theme)| *theme)
+            .ok_or_else(|| {
+                let names: Vec<&str> = THEMES.iter().map(|(name, _)| *name).collect();
+                format!("Unknown theme \"{name}\". Use one of {}.", names.join(", "))
+            })
+    }
+
+    /// Get the color to draw a score label with
+    pub fn label_color(&self, default: Color) -> Color {
+        self.labels.unwrap_or(default)
+    }
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+/// The built-in themes and their names
+pub const THEMES: &[(&str, Theme)] = &[
+    (
+        "dark",
+        Theme {
+            completed: COMPLETED,
+            uncompleted: UNCOMPLETED,
+            error: ERROR,
+            labels: Some(rgb(97, 175, 239)),
+        },
+    ),
+    (
+        "light",
+        Theme {
+            completed: gray(20),
+            uncompleted: gray(160),
+            error: rgb(200, 0, 0),
+            labels: Some(rgb(0, 90, 180)),
+        },
+    ),
+    (
+        "solarized",
+        Theme {
+            completed: rgb(147, 161, 161),
+            uncompleted: rgb(88, 110, 117),
+            error: rgb(220, 50, 47),
+            labels: Some(rgb(38, 139, 210)),
+        },
+    ),
+    (
+        "monokai",
+        Theme {
+            completed: rgb(248, 248, 242),
+            uncompleted: rgb(117, 113, 94),
+            error: rgb(249, 38, 114),
+            labels: Some(rgb(166, 226, 46)),
+        },
+    ),
+];
+
+/// The wpm that separate slow, average, and fast typing speeds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WpmTiers {
+    /// Anything slower than this is slow
+    pub slow: f32,
+    /// Anything faster than this is fast
+    pub fast: f32,
+}
+
+impl Default for WpmTiers {
+    fn default() -> Self {
+        Self {
+            slow: 40.0,
+            fast: 80.0,
+        }
+    }
+}
+
+impl WpmTiers {
+    /// Get the color to draw {wpm} in: red if slow, yellow if average, or green if fast
+    pub fn color(&self, wpm: f32) -> Color {
+        if wpm < self.slow {
+            Color::Red
+        } else if wpm > self.fast {
+            Color::Green
+        } else {
+            Color::Yellow
+        }
+    }
+}
+
+/// Parse a hex color like "#e60000" or a named color like "red"
+pub fn parse_color(s: &str) -> Result<Color, String> {
+    let invalid = || {
+        format!("Invalid color \"{s}\". Use a hex color like \"#e60000\" or a name like \"red\".")
+    };
+    match s.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.is_ascii() => {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+            Ok(Color::Rgb {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            })
+        }
+        Some(_) => Err(invalid()),
+        None => Color::try_from(s).map_err(|_| invalid()),
+    }
+}
+
+/// Get the number of columns a string takes up when drawn in a terminal
+pub fn display_width(s: &str) -> usize {
+    // each grapheme is drawn in at most two columns even if made of several wide characters
+    s.graphemes(true)
+        .map(|grapheme| grapheme.width().min(2))
+        .sum()
+}
+
 fn join<T>(x: T) -> String
 where
     T: IntoIterator,
@@ -32,14 +255,416 @@ where
         .unwrap_or_default()
 }
 
-/// Get a random word from the list of words
-fn next_word() -> &'static str {
-    WORDS[rand::random::<usize>() % WORDS.len()]
+/// Make sure every entry of a word list is a single word
+/// Entries containing whitespace would break word counting which splits on spaces
+pub fn validate_words<S: AsRef<str>>(words: &[S]) -> Result<(), String> {
+    if words.is_empty() {
+        return Err("The word list is empty.".into());
+    }
+    match words
+        .iter()
+        .map(AsRef::as_ref)
+        .find(|word| word.is_empty() || word.contains(char::is_whitespace))
+    {
+        Some(word) => Err(format!(
+            "The word list entry \"{}\" is not a single word.",
+            word
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Read a list of words separated by whitespace from a file
+/// Returns a message describing the problem if the file cannot be read or has no words
+pub fn read_words(path: &Path) -> Result<Vec<String>, String> {
+    let path_name = path.display();
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read word file \"{path_name}\" because {e}."))?;
+    // curly apostrophes in contractions like "don’t" are replaced so they can be typed
+    let words: Vec<String> = contents.split_whitespace().map(plain_quotes).collect();
+    if words.is_empty() {
+        Err(format!("The word file \"{path_name}\" has no words."))
+    } else {
+        Ok(words)
+    }
+}
+
+/// Parse lines of a word and a positive weight separated by a tab
+/// Blank lines are skipped and the weights of repeated words are added together
+/// Returns the words in the order they first appear along with the weight of each word
+pub fn parse_weighted_words(contents: &str) -> Result<(Vec<String>, HashMap<String, u32>), String> {
+    let mut words = Vec::new();
+    let mut weights: HashMap<String, u32> = HashMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid =
+            |reason: &str| format!("Invalid weighted word on line {} because {reason}.", i + 1);
+        let (word, weight) = line
+            .trim_end_matches('\r')
+            .split_once('\t')
+            .ok_or_else(|| invalid("there is no tab between the word and its weight"))?;
+        let word = plain_quotes(word.trim());
+        if word.is_empty() || word.contains(char::is_whitespace) {
+            return Err(invalid(&format!("\"{word}\" is not a single word")));
+        }
+        let weight: u32 = match weight.trim().parse() {
+            Ok(weight) if weight > 0 => weight,
+            _ => {
+                return Err(invalid(&format!(
+                    "\"{}\" is not a positive whole number",
+                    weight.trim()
+                )))
+            }
+        };
+        match weights.get_mut(&word) {
+            Some(total) => *total = total.saturating_add(weight),
+            None => {
+                weights.insert(word.clone(), weight);
+                words.push(word);
+            }
+        }
+    }
+    if words.is_empty() {
+        return Err("The weighted word file has no words.".into());
+    }
+    Ok((words, weights))
+}
+
+/// Read a file of words and weights made for `parse_weighted_words`
+pub fn read_weighted_words(path: &Path) -> Result<(Vec<String>, HashMap<String, u32>), String> {
+    let path_name = path.display();
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read weighted word file \"{path_name}\" because {e}."))?;
+    parse_weighted_words(&contents)
+        .map_err(|e| format!("Invalid weighted word file \"{path_name}\". {e}"))
+}
+
+/// Make sure the bundled lists of words are valid
+pub fn validate_word_list() -> Result<(), String> {
+    LANGUAGES
+        .iter()
+        .try_for_each(|(_, words)| validate_words(words))
+}
+
+/// How likely each word of a list is to be picked compared to the others
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Weighting {
+    /// Every word is as likely to be picked
+    #[default]
+    Even,
+    /// The list is sorted from most to least common so each word is weighted by its rank
+    ByRank,
+    /// Each word is weighted by its entry and words without one are never picked
+    ByWord(HashMap<String, u32>),
+}
+
+impl Weighting {
+    /// Get the weight of each word, or None if every word is as likely to be picked
+    fn weights(&self, words: &[String]) -> Option<Vec<f64>> {
+        match self {
+            Weighting::Even => None,
+            Weighting::ByRank => Some((1..=words.len()).map(|rank| 1.0 / rank as f64).collect()),
+            Weighting::ByWord(weights) => Some(
+                words
+                    .iter()
+                    .map(|word| weights.get(word).map_or(0.0, |&weight| weight as f64))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Which lengths of words are used when generating lines
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    /// Words of at most 4 characters
+    Easy,
+    /// Words of 5 to 7 characters
+    Medium,
+    /// Words of at least 8 characters
+    Hard,
+}
+
+impl Difficulty {
+    /// Whether or not a word's length is allowed by the difficulty
+    pub fn allows(self, word: &str) -> bool {
+        let len = word.chars().count();
+        match self {
+            Difficulty::Easy => len <= 4,
+            Difficulty::Medium => (5..=7).contains(&len),
+            Difficulty::Hard => len >= 8,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("Invalid difficulty \"{s}\".")),
+        }
+    }
+}
+
+/// Get the words allowed by a difficulty
+/// Returns all of the words if none of them are allowed
+pub fn filter_words(words: &[String], difficulty: Difficulty) -> Vec<String> {
+    let filtered: Vec<_> = words
+        .iter()
+        .filter(|word| difficulty.allows(word))
+        .cloned()
+        .collect();
+    if filtered.is_empty() {
+        words.to_vec()
+    } else {
+        filtered
+    }
+}
+
+/// Get the words with at least {min} and at most {max} characters
+/// Returns a message describing the limits if none of the words fit them
+pub fn filter_lengths(
+    words: &[String],
+    min: Option<usize>,
+    max: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let filtered: Vec<_> = words
+        .iter()
+        .filter(|word| {
+            let len = word.chars().count();
+            min.is_none_or(|min| len >= min) && max.is_none_or(|max| len <= max)
+        })
+        .cloned()
+        .collect();
+    if !filtered.is_empty() {
+        return Ok(filtered);
+    }
+    let limits = match (min, max) {
+        (Some(min), Some(max)) => format!("between {} and {}", min, max),
+        (Some(min), None) => format!("at least {}", min),
+        (None, Some(max)) => format!("at most {}", max),
+        (None, None) => return Err("There are no words to use.".into()),
+    };
+    Err(format!(
+        "There are no words {} characters long. Please use --min-word-length and --max-word-length to allow more words.",
+        limits
+    ))
+}
+
+/// Settings used when generating new lines
+#[derive(Clone, Debug)]
+pub struct Generator {
+    /// The words lines are made of
+    pub words: Vec<String>,
+    /// How likely each word is to be picked
+    pub weighting: Weighting,
+    /// The weights of {words} built the first time a word is picked, to be reset if either changes
+    pub distribution: Option<WeightedIndex<f64>>,
+    /// Words that have been learned and should appear less often
+    pub learned: HashSet<String>,
+    /// Whether or not to add punctuation and capitalization to words
+    pub punctuation: bool,
+    /// Whether or not to replace some words with numbers
+    pub numbers: bool,
+    /// The fraction of words to capitalize, between 0 and 1
+    pub capitalize_ratio: f64,
+    /// Whether or not to generate lines of code instead of words
+    pub code: bool,
+    /// Whether or not to avoid repeating a word within a line
+    pub no_repeats: bool,
+    /// The most characters a line can have, or None for {LINE_LEN} words per line
+    pub width: Option<usize>,
+    /// Whether or not the last generated word did not end a sentence
+    pub mid_sentence: bool,
+    /// Where all randomness comes from so a seed always generates the same lines
+    pub rng: StdRng,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            words: WORDS.iter().map(|word| word.to_string()).collect(),
+            weighting: Weighting::Even,
+            distribution: None,
+            learned: HashSet::new(),
+            punctuation: false,
+            numbers: false,
+            capitalize_ratio: 0.0,
+            code: false,
+            no_repeats: false,
+            width: None,
+            mid_sentence: false,
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+/// Capitalize the first letter of a word
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Capitalize a word if it starts a sentence and sometimes follow it with punctuation
+fn punctuate(word: &str, generator: &mut Generator) -> String {
+    let mut word = if generator.mid_sentence {
+        word.to_string()
+    } else {
+        capitalize(word)
+    };
+    generator.mid_sentence = true;
+    if generator.rng.gen::<f32>() < PUNCTUATION_CHANCE {
+        let mark = PUNCTUATION[generator.rng.gen_range(0..PUNCTUATION.len())];
+        word.push(mark);
+        generator.mid_sentence = mark == ',';
+    }
+    word
+}
+
+/// Get the index of a random word by the generator's weighting
+/// The distribution of the weights is only built once so picking a word does not go through every word
+fn pick_index(generator: &mut Generator) -> usize {
+    if generator.distribution.is_none() {
+        generator.distribution = generator
+            .weighting
+            .weights(&generator.words)
+            .and_then(|weights| WeightedIndex::new(weights).ok());
+    }
+    match &generator.distribution {
+        Some(distribution) => distribution.sample(&mut generator.rng),
+        // words are picked evenly if none of them have a weight
+        None => generator.rng.gen_range(0..generator.words.len()),
+    }
+}
+
+/// Get a random word, avoiding the words already {picked} for the line if the generator has no repeats
+/// Picked words are drawn again so the rest of the words keep their chances of being picked
+fn draw_word(generator: &mut Generator, picked: &[String]) -> String {
+    let mut index = pick_index(generator);
+    if !generator.no_repeats {
+        return generator.words[index].clone();
+    }
+    for _ in 0..MAX_REDRAWS {
+        if !picked.contains(&generator.words[index]) {
+            return generator.words[index].clone();
+        }
+        index = pick_index(generator);
+    }
+    let words = &generator.words;
+    // every draw can be a picked word when only a few words are left
+    // if every word has been picked only the last picked word is left out so it is not repeated right away
+    let left_out = |skip: &[String]| -> Vec<usize> {
+        (0..words.len())
+            .filter(|&i| !skip.contains(&words[i]))
+            .collect()
+    };
+    let mut left = left_out(picked);
+    if left.is_empty() {
+        left = left_out(&picked[picked.len() - 1..]);
+    }
+    match left.len() {
+        0 => words[index].clone(),
+        len => words[left[generator.rng.gen_range(0..len)]].clone(),
+    }
+}
+
+/// Get a random word, giving learned words a lower chance of being picked
+/// Words already {picked} for the line are avoided if the generator has no repeats
+fn next_word(generator: &mut Generator, picked: &[String]) -> String {
+    let word = draw_word(generator, picked);
+    if generator.learned.contains(&word) && generator.rng.gen::<f32>() < LEARNED_SKIP_CHANCE {
+        draw_word(generator, picked)
+    } else {
+        word
+    }
+}
+
+/// Replace a word with a random number some of the time
+fn replace_with_number(word: &str, rng: &mut impl Rng) -> String {
+    if rng.gen_bool(NUMBER_CHANCE) {
+        rng.gen_range(0..=MAX_NUMBER).to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+/// Get a random word, replacing it with a number if numbers are enabled
+/// Some words are capitalized if there is a capitalize ratio
+/// The word is added to the words {picked} for the line before it is changed
+fn generate_word(generator: &mut Generator, picked: &mut Vec<String>) -> String {
+    let word = next_word(generator, picked);
+    picked.push(word.clone());
+    let word = if generator.numbers {
+        replace_with_number(&word, &mut generator.rng)
+    } else {
+        word
+    };
+    // the rng is only used when needed so seeds generate the same lines as before
+    let ratio = generator.capitalize_ratio;
+    if ratio > 0.0 && generator.rng.gen_bool(ratio.min(1.0)) {
+        capitalize(&word)
+    } else {
+        word
+    }
+}
+
+/// Get a line of random words that fits within the generator's width
+/// or is comprised of {LINE_LEN} words if there is no width
+fn next_line(generator: &mut Generator) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut picked: Vec<String> = Vec::new();
+    let mut len = 0;
+    loop {
+        if generator.width.is_none() && words.len() >= LINE_LEN {
+            break;
+        }
+        let word = generate_word(generator, &mut picked);
+        let separator = usize::from(!words.is_empty());
+        // leave room for any punctuation that might be added
+        let needed = separator + word.chars().count() + usize::from(generator.punctuation);
+        if let Some(width) = generator.width {
+            if !words.is_empty() && len + needed > width {
+                break;
+            }
+        }
+        let word = if generator.punctuation {
+            punctuate(&word, generator)
+        } else {
+            word
+        };
+        len += separator + word.chars().count();
+        words.push(word);
+    }
+    join(words)
+}
+
+/// Get a random line of code that fits within the generator's width if one does
+fn next_code_line(generator: &mut Generator) -> String {
+    let fits: Vec<&str> = CODE
+        .iter()
+        .copied()
+        .filter(|line| {
+            generator
+                .width
+                .is_none_or(|width| line.chars().count() <= width)
+        })
+        .collect();
+    let lines = if fits.is_empty() { CODE } else { &fits };
+    lines[generator.rng.gen_range(0..lines.len())].to_string()
 }
 
-/// Get a line comprised of {LINE_LEN} random words
-fn next_line() -> String {
-    join(std::iter::repeat_with(next_word).take(LINE_LEN))
+/// Get the spaces at the start of a line
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches(' ').len()]
 }
 
 /// A struct representing expected input and actual input
@@ -47,11 +672,15 @@ fn next_line() -> String {
 pub struct Line {
     buffer: String,
     expected: String,
+    keystrokes: u32,
+    errors: u32,
+    fixed: u32,
+    indent: usize,
 }
 
 impl Default for Line {
     fn default() -> Self {
-        Line::new()
+        Line::new(&mut Generator::default())
     }
 }
 
@@ -60,41 +689,83 @@ impl Line {
     pub const EMPTY: Self = Self {
         buffer: String::new(),
         expected: String::new(),
+        keystrokes: 0,
+        errors: 0,
+        fixed: 0,
+        indent: 0,
     };
 
-    pub fn new() -> Self {
+    /// Create a new Line of random words
+    /// Lines of code start with their indentation already typed
+    pub fn new(generator: &mut Generator) -> Self {
+        if generator.code {
+            let expected = next_code_line(generator);
+            let indent = indentation(&expected);
+            return Self {
+                buffer: indent.to_string(),
+                indent: indent.len(),
+                expected,
+                ..Self::EMPTY
+            };
+        }
         Self {
-            buffer: String::new(),
-            expected: next_line(),
+            expected: next_line(generator),
+            ..Self::EMPTY
         }
     }
 
-    /// Create a new Line using {LINE_LEN} words of a string
+    /// Create a new Line using the words of a string that fit within {width} columns
+    /// or {LINE_LEN} words if there is no width
+    /// If {sentences} is true the line ends early after the last sentence that fits
     /// Leaves remaining words in string
-    pub fn from_quote(string: &mut String) -> Self {
-        let mut it = string.split(' ');
+    pub fn from_quote(string: &mut String, sentences: bool, width: Option<usize>) -> Self {
+        let words: Vec<&str> = string.split(' ').collect();
+        let mut len = match width {
+            Some(width) => fitting_words(&words, width),
+            None => words.len().min(LINE_LEN),
+        };
+        if sentences && words.len() > len {
+            if let Some(end) = (MIN_SENTENCE_WORDS..=len)
+                .rev()
+                .find(|&end| ends_sentence(words[end - 1]))
+            {
+                len = end;
+            }
+        }
         let res = Line {
-            expected: join((&mut it).take(LINE_LEN)),
-            ..Self::new()
+            expected: plain_quotes(&words[..len].join(" ")),
+            ..Self::EMPTY
         };
-        *string = join(it);
+        *string = words[len..].join(" ");
         res
     }
 
+    /// Get the text that is expected to be typed
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
     /// Get the x position for moving the cursor
     pub fn index(&self) -> usize {
-        self.buffer.len()
+        display_width(&self.buffer)
+    }
+
+    /// Get the number of characters that have been typed
+    pub fn typed_len(&self) -> usize {
+        self.buffer.chars().count()
     }
 
     /// Calculate the number of correctly completed words
+    /// Runs of spaces such as indentation do not count as words
     pub fn word_count(&self) -> u32 {
         let buffer: Vec<char> = self.buffer.chars().chain([' ']).collect();
         let expected: Vec<char> = self.expected.chars().collect();
         let mut word_correct = true;
+        let mut in_word = false;
         let mut count = 0;
         for i in 0..buffer.len() {
             if i >= expected.len() {
-                if word_correct {
+                if word_correct && in_word {
                     count += 1;
                 }
                 break;
@@ -102,10 +773,13 @@ impl Line {
             // TODO consider making '-' another option here
             // this would count "self-concious" as two words rather than one
             if expected[i] == ' ' {
-                if word_correct {
+                if word_correct && in_word {
                     count += 1;
                 }
                 word_correct = true;
+                in_word = false;
+            } else {
+                in_word = true;
             }
             if buffer[i] != expected[i] {
                 word_correct = false;
@@ -114,48 +788,365 @@ impl Line {
         count
     }
 
+    /// Get each expected word that has been reached and whether it was typed correctly
+    /// Words are checked by position the same way `word_count` counts them
+    pub fn word_marks(&self) -> Vec<(&str, bool)> {
+        let buffer: Vec<char> = self.buffer.chars().chain([' ']).collect();
+        let expected: Vec<char> = self.expected.chars().collect();
+        let typed = buffer.len() - 1;
+        let mut marks = Vec::new();
+        let mut start = 0;
+        for word in self.expected.split(' ') {
+            if start >= typed {
+                break;
+            }
+            let end = start + word.chars().count();
+            // runs of spaces are not words
+            if !word.is_empty() {
+                let correct =
+                    (start..=end).all(|i| buffer.get(i) == Some(expected.get(i).unwrap_or(&' ')));
+                marks.push((word, correct));
+            }
+            start = end + 1;
+        }
+        marks
+    }
+
+    /// Calculate the number of words typed whether or not they are correct
+    pub fn raw_word_count(&self) -> u32 {
+        self.buffer
+            .split(' ')
+            .filter(|token| !token.is_empty())
+            .count() as u32
+    }
+
+    /// Calculate the number of characters typed that match the expected characters
+    pub fn char_count(&self) -> u32 {
+        self.buffer
+            .chars()
+            .zip(self.expected.chars())
+            .skip(self.indent)
+            .filter(|(typed, expected)| typed == expected)
+            .count() as u32
+    }
+
+    /// Get the expected word the cursor is on along with what has been typed of it
+    pub fn current_word(&self) -> (&str, &str) {
+        let index = self.buffer.matches(' ').count();
+        let expected = self.expected.split(' ').nth(index).unwrap_or_default();
+        let typed = self.buffer.rsplit(' ').next().unwrap_or_default();
+        (expected, typed)
+    }
+
+    /// return true if what has been typed of the word the cursor is on matches the whole word
+    pub fn current_word_correct(&self) -> bool {
+        let (expected, typed) = self.current_word();
+        expected == typed
+    }
+
+    /// Get the range of characters of the expected word the cursor is on
+    /// Returns None if the cursor is past the end of the line
+    pub fn current_word_bounds(&self) -> Option<Range<usize>> {
+        let expected: Vec<char> = self.expected.chars().collect();
+        let cursor = self.buffer.chars().count();
+        if cursor >= expected.len() {
+            return None;
+        }
+        let start = expected[..cursor]
+            .iter()
+            .rposition(|&ch| ch == ' ')
+            .map_or(0, |i| i + 1);
+        let end = expected[cursor..]
+            .iter()
+            .position(|&ch| ch == ' ')
+            .map_or(expected.len(), |i| cursor + i);
+        Some(start..end)
+    }
+
+    /// Get the character that is expected to be typed next if there is one
+    pub fn next_expected(&self) -> Option<char> {
+        self.expected.chars().nth(self.buffer.chars().count())
+    }
+
+    /// return true if adding {ch} would count as an error
+    /// Nothing is a mistake once the line is done since characters are ignored
+    pub fn is_mistake(&self, ch: char) -> bool {
+        !self.done() && self.next_expected() != Some(ch)
+    }
+
     /// remove one character if it exists
     pub fn backspace(&mut self) {
-        self.buffer.pop();
+        self.remove_from(self.typed_len().saturating_sub(1));
+    }
+
+    /// remove trailing spaces and then the rest of the current word
+    pub fn backspace_word(&mut self) {
+        let trimmed = self.buffer.trim_end_matches(' ');
+        let start = trimmed.rfind(' ').map_or(0, |i| i + 1);
+        self.remove_from(self.buffer[..start].chars().count());
+    }
+
+    /// Remove the typed characters from the {start}th character on
+    /// Any incorrect characters that are removed count as fixed
+    /// Indentation that was typed already is never removed
+    fn remove_from(&mut self, start: usize) {
+        let start = start.max(self.indent);
+        let expected: Vec<char> = self.expected.chars().collect();
+        let fixed = self
+            .buffer
+            .chars()
+            .enumerate()
+            .skip(start)
+            .filter(|&(i, ch)| expected.get(i) != Some(&ch))
+            .count();
+        self.fixed += fixed as u32;
+        let end = self
+            .buffer
+            .char_indices()
+            .nth(start)
+            .map_or(self.buffer.len(), |(i, _)| i);
+        self.buffer.truncate(end);
     }
 
-    /// Returns true if a word has been finshed
-    pub fn add_char(&mut self, ch: char) {
+    /// Add a character to the buffer, counting it as an error if it was not expected
+    /// Characters are ignored once the line is done
+    /// Returns false if the character was ignored
+    pub fn add_char(&mut self, ch: char) -> bool {
+        if self.done() {
+            return false;
+        }
+        self.keystrokes += 1;
+        if self.is_mistake(ch) {
+            self.errors += 1;
+        }
         self.buffer.push(ch);
+        true
+    }
+
+    /// Get the number of characters typed and how many of them were incorrect
+    pub fn keystrokes(&self) -> (u32, u32) {
+        (self.keystrokes, self.errors)
+    }
+
+    /// Get the number of incorrect characters that were removed with backspace
+    pub fn fixed(&self) -> u32 {
+        self.fixed
     }
 
-    /// draw the line to provided stdout
-    pub fn draw(&self, stdout: &mut io::Stdout) -> crossterm::Result<()> {
+    /// Get each character to draw styled by whether it was typed correctly
+    /// The rest of the word the cursor is on is underlined if {cursor} is true
+    /// Anything past {width} columns is cut off and marked with an ellipsis
+    fn styled_chars(
+        &self,
+        width: usize,
+        theme: &Theme,
+        cursor: bool,
+        highlight_errors: bool,
+    ) -> Vec<StyledContent<char>> {
         let buffer: Vec<char> = self.buffer.chars().collect();
         let expected: Vec<char> = self.expected.chars().collect();
-        for i in 0..buffer.len().max(expected.len()) {
-            let ch = if i >= buffer.len() {
-                expected[i].with(UNCOMPLETED)
-            } else if i >= expected.len() {
-                buffer[i].with(ERROR)
-            } else {
-                let color = if buffer[i] == expected[i] {
-                    COMPLETED
-                } else {
-                    ERROR
-                };
-                if buffer[i] == ' ' && color == ERROR {
-                    buffer[i].on(color)
+        let current = self.current_word_bounds().filter(|_| cursor);
+        let mut chars: Vec<StyledContent<char>> = (0..buffer.len().max(expected.len()))
+            .map(|i| {
+                if i >= buffer.len() {
+                    let ch = expected[i].with(theme.uncompleted);
+                    if current.as_ref().is_some_and(|word| word.contains(&i)) {
+                        ch.underlined()
+                    } else {
+                        ch
+                    }
                 } else {
-                    buffer[i].with(color)
+                    style_typed(buffer[i], expected.get(i).copied(), theme, highlight_errors)
                 }
-            };
-            queue!(stdout, PrintStyledContent(ch))?;
+            })
+            .collect();
+        fit_chars(&mut chars, width, theme);
+        chars
+    }
+
+    /// draw the line to provided stdout in the colors of {theme}, fitting it within {width} columns
+    /// Incorrect characters are drawn in the error color only if {highlight_errors} is true
+    /// The character at {pace} is marked to show where the pacer is
+    pub fn draw(
+        &self,
+        stdout: &mut impl Write,
+        width: usize,
+        theme: &Theme,
+        highlight_errors: bool,
+        pace: Option<usize>,
+    ) -> crossterm::Result<()> {
+        let mut chars = self.styled_chars(width, theme, false, highlight_errors);
+        mark_pace(&mut chars, pace);
+        draw_chars(stdout, chars)
+    }
+
+    /// draw the line like `draw` while highlighting the word the cursor is on
+    /// A {caret} glyph is drawn at the typing position if there is one
+    pub fn draw_with_cursor(
+        &self,
+        stdout: &mut impl Write,
+        width: usize,
+        theme: &Theme,
+        highlight_errors: bool,
+        pace: Option<usize>,
+        caret: Option<char>,
+    ) -> crossterm::Result<()> {
+        let mut chars = self.styled_chars(width, theme, true, highlight_errors);
+        mark_pace(&mut chars, pace);
+        if let Some(caret) = caret {
+            place_caret(
+                &mut chars,
+                self.typed_len(),
+                caret.with(theme.completed),
+                width,
+            );
         }
-        queue!(stdout, cursor::MoveToNextLine(1))
+        draw_chars(stdout, chars)
+    }
+
+    /// Get the number of columns the line takes up before it is fitted to the screen
+    /// Typed characters are drawn in place of the expected ones
+    pub fn drawn_width(&self) -> usize {
+        let rest: String = self.expected.chars().skip(self.typed_len()).collect();
+        display_width(&self.buffer) + display_width(&rest)
+    }
+
+    /// Get the column of the cursor, keeping it within {width} columns
+    pub fn column(&self, width: usize) -> usize {
+        self.index().min(width.saturating_sub(1))
+    }
+
+    /// Whether or not everything typed so far matches the expected text
+    pub fn is_clean(&self) -> bool {
+        self.expected.starts_with(&self.buffer)
     }
 
     /// return true if all of the expected input has been completed
     pub fn done(&self) -> bool {
-        self.index() >= self.expected.len()
+        self.typed_len() >= self.expected.chars().count()
+    }
+}
+
+/// Replace typographic quotes and apostrophes with the ones on a keyboard
+fn plain_quotes(s: &str) -> String {
+    s.chars()
+        .map(|ch| match ch {
+            '‘' | '’' | '‚' | '′' => '\'',
+            '“' | '”' | '„' | '″' => '"',
+            ch => ch,
+        })
+        .collect()
+}
+
+/// Count how many of the first {words} fit on a line {width} columns wide
+/// At least one word is always counted so every line makes progress
+fn fitting_words(words: &[&str], width: usize) -> usize {
+    let mut len = 0;
+    let count = words
+        .iter()
+        .take_while(|word| {
+            let separator = usize::from(len > 0);
+            len += separator + display_width(word);
+            len <= width
+        })
+        .count();
+    count.max(1).min(words.len())
+}
+
+/// Whether {word} is the last word of a sentence like "end." or "really?\""
+fn ends_sentence(word: &str) -> bool {
+    word.trim_end_matches(['"', '\'', ')', '”', '’'])
+        .ends_with(['.', '?', '!'])
+}
+
+/// Get the number of columns before a line of {len} characters that centers it within {width} columns
+pub fn center_padding(len: usize, width: usize) -> usize {
+    width.saturating_sub(len) / 2
+}
+
+/// Style a typed character by whether it matches the {expected} character
+/// Incorrect spaces are highlighted so they can be seen
+/// Without {highlight_errors} incorrect characters look like correct ones
+fn style_typed(
+    typed: char,
+    expected: Option<char>,
+    theme: &Theme,
+    highlight_errors: bool,
+) -> StyledContent<char> {
+    if expected == Some(typed) || !highlight_errors {
+        typed.with(theme.completed)
+    } else if typed == ' ' {
+        typed.on(theme.error)
+    } else {
+        typed.with(theme.error)
+    }
+}
+
+/// Give the character at {pace} a background to mark where the pacer is
+/// Characters that already have a background such as incorrect spaces are left alone
+fn mark_pace(chars: &mut [StyledContent<char>], pace: Option<usize>) {
+    if let Some(ch) = pace.and_then(|pace| chars.get_mut(pace)) {
+        let style = ch.style_mut();
+        if style.background_color.is_none() {
+            style.background_color = Some(PACE);
+        }
+    }
+}
+
+/// Get the number of columns a styled character takes up
+fn columns(ch: &StyledContent<char>) -> usize {
+    ch.content().width().unwrap_or(0)
+}
+
+/// Get the number of columns styled characters take up
+fn total_columns(chars: &[StyledContent<char>]) -> usize {
+    chars.iter().map(columns).sum()
+}
+
+/// Cut off characters past {width} columns and mark where they were cut off
+/// Wide characters such as CJK take up two columns
+fn fit_chars(chars: &mut Vec<StyledContent<char>>, width: usize, theme: &Theme) {
+    if total_columns(chars) <= width {
+        return;
+    }
+    // leave a column for the overflow marker
+    let mut used = 0;
+    let fits = chars
+        .iter()
+        .take_while(|ch| {
+            used += columns(ch);
+            used < width
+        })
+        .count();
+    chars.truncate(fits);
+    if width > 0 {
+        chars.push(OVERFLOW.with(theme.error));
+    }
+}
+
+/// Draw the {caret} glyph in place of the character at {index} so the rest of the line stays put
+/// Past the end of the line the caret is added if it fits within {width} columns
+fn place_caret(
+    chars: &mut Vec<StyledContent<char>>,
+    index: usize,
+    caret: StyledContent<char>,
+    width: usize,
+) {
+    if let Some(ch) = chars.get_mut(index) {
+        *ch = caret;
+    } else if total_columns(chars) < width {
+        chars.push(caret);
     }
 }
 
+/// Print styled characters followed by a new line
+fn draw_chars(stdout: &mut impl Write, chars: Vec<StyledContent<char>>) -> crossterm::Result<()> {
+    for ch in chars {
+        queue!(stdout, PrintStyledContent(ch))?;
+    }
+    queue!(stdout, cursor::MoveToNextLine(1))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,68 +1160,939 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_words_test() {
+        assert!(validate_word_list().is_ok());
+        assert!(validate_words(&["one", "two", "three"]).is_ok());
+        assert!(validate_words::<&str>(&[]).is_err());
+        assert!(validate_words(&["one", ""]).is_err());
+        assert_eq!(
+            validate_words(&["one", "ice cream", "two"]),
+            Err("The word list entry \"ice cream\" is not a single word.".into())
+        );
+        assert!(validate_words(&["tab\tseparated"]).is_err());
+    }
+
     #[test]
     fn line_new_test() {
         for _ in 0..100 {
-            let line = Line::new();
+            let line = Line::new(&mut Generator::default());
             assert_eq!(line.buffer, "");
             assert_ne!(line.expected, "");
         }
     }
 
     #[test]
-    fn line_from_quote_test() {
-        let mut s = "This is a quote".to_string();
-        let s_clone = s.clone();
-        let line = Line::from_quote(&mut s);
-        assert_eq!(s, "");
-        assert_eq!(line.expected, s_clone);
-        let offset = 3;
-        s = join(1..=(LINE_LEN + offset));
-        let line = Line::from_quote(&mut s);
-        assert_eq!(s, join((LINE_LEN + 1)..=(LINE_LEN + offset)));
-        assert_eq!(line.expected, join(1..=LINE_LEN));
+    fn next_word_test() {
+        let mut generator = Generator {
+            learned: WORDS[..WORDS.len() / 2]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ..Default::default()
+        };
+        let learned = (0..10000)
+            .filter(|_| {
+                let word = next_word(&mut generator, &[]);
+                generator.learned.contains(&word)
+            })
+            .count();
+        // without the bias about half of the words would be learned words
+        assert!(learned < 4000, "{} learned words picked", learned);
+        let mut generator = Generator {
+            learned: WORDS.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+        assert!(WORDS.contains(&next_word(&mut generator, &[]).as_str()));
     }
 
     #[test]
-    fn line_empty_test() {
-        let line = Line::EMPTY;
-        assert_eq!(line.buffer, "");
-        assert_eq!(line.expected, "");
+    fn parse_weighted_words_test() {
+        let (words, weights) =
+            parse_weighted_words("the\t50\n\nrust\t 3\r\ndon’t\t1\nthe\t10\n").unwrap();
+        assert_eq!(words, ["the", "rust", "don't"]);
+        assert_eq!(weights["the"], 60);
+        assert_eq!(weights["rust"], 3);
+        assert_eq!(weights["don't"], 1);
+        for (contents, line, problem) in [
+            ("the 50", 1, "no tab"),
+            ("the\t50\nrust", 2, "no tab"),
+            ("two words\t5", 1, "\"two words\" is not a single word"),
+            ("\t5", 1, "\"\" is not a single word"),
+            ("the\tmany", 1, "\"many\" is not a positive whole number"),
+            ("the\t0", 1, "\"0\" is not a positive whole number"),
+            ("the\t-2", 1, "\"-2\" is not a positive whole number"),
+        ] {
+            let e = parse_weighted_words(contents).unwrap_err();
+            assert!(e.contains(&format!("line {line} ")), "{}", e);
+            assert!(e.contains(problem), "{}", e);
+        }
+        assert!(parse_weighted_words("\n\n")
+            .unwrap_err()
+            .contains("has no words"));
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "a\t1\nb\tx").unwrap();
+        let e = read_weighted_words(file.path()).unwrap_err();
+        assert!(e.contains("line 2"), "{}", e);
+        assert!(e.contains(&file.path().display().to_string()), "{}", e);
+        let missing = Path::new("this/file/does/not/exist.tsv");
+        assert!(read_weighted_words(missing)
+            .unwrap_err()
+            .contains("Could not read"));
     }
 
     #[test]
-    fn line_index_test() {
-        let mut line = Line::new();
-        line.buffer = "abc 12".to_string();
-        assert_eq!(line.index(), 6);
-        line.buffer = "123".to_string();
-        assert_eq!(line.index(), 3);
-        line.buffer = "This one is pretty long".to_string();
-        assert_eq!(line.index(), 23);
+    fn weighted_word_file_test() {
+        let (words, weights) = parse_weighted_words("rare\t1\ncommon\t3\nnever\t1").unwrap();
+        let mut generator = Generator {
+            words: words[..2].to_vec(),
+            weighting: Weighting::ByWord(weights),
+            rng: StdRng::seed_from_u64(7),
+            ..Default::default()
+        };
+        let common = (0..10000)
+            .filter(|_| next_word(&mut generator, &[]) == "common")
+            .count();
+        // about 3 of every 4 words should be the common word
+        assert!((7200..7800).contains(&common), "{} common words", common);
+        // with no repeats the weights still apply to the words left to pick from
+        generator.no_repeats = true;
+        assert_eq!(next_word(&mut generator, &["common".into()]), "rare");
+        // the weights are only worked out once for the list of words
+        assert!(generator.distribution.is_some());
+        // words missing from the weights are only picked when no word has a weight
+        generator.words = vec!["other".into()];
+        generator.distribution = None;
+        assert_eq!(next_word(&mut generator, &[]), "other");
     }
 
     #[test]
-    fn line_word_count_test() {
-        for (b, e, count) in [
-            ("a b d", "a b c d", 2),
-            ("a b c", "a b c d", 3),
-            ("This is a quote!", "This is a quote!", 4),
-            ("This is not a quote!", "This is a quote!", 2),
-        ] {
+    fn weighted_word_test() {
+        let count = |weighting: Weighting, words: &[&str]| {
+            let mut generator = Generator {
+                weighting,
+                ..Default::default()
+            };
+            (0..10000)
+                .filter(|_| words.contains(&next_word(&mut generator, &[]).as_str()))
+                .count()
+        };
+        let common = &WORDS[..10];
+        let rare = &WORDS[WORDS.len() - 10..];
+        let common_weighted = count(Weighting::ByRank, common);
+        let rare_weighted = count(Weighting::ByRank, rare);
+        // about 5000 common words and 90 rare words are expected
+        assert!(
+            common_weighted > rare_weighted * 10,
+            "{} common and {} rare words picked",
+            common_weighted,
+            rare_weighted
+        );
+        // uniformly picked words are about as likely to be common as rare
+        assert!(count(Weighting::Even, common) < common_weighted / 2);
+        assert!(count(Weighting::Even, rare) > rare_weighted * 2);
+    }
+
+    #[test]
+    fn difficulty_test() {
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let words = filter_words(&Generator::default().words, difficulty);
+            assert!(!words.is_empty());
+            let mut generator = Generator {
+                words,
+                ..Default::default()
+            };
+            for _ in 0..20 {
+                for word in next_line(&mut generator).split(' ') {
+                    assert!(difficulty.allows(word), "{:?} {}", difficulty, word);
+                }
+            }
+        }
+        assert!(Difficulty::Easy.allows("four"));
+        assert!(!Difficulty::Easy.allows("fives"));
+        assert!(Difficulty::Medium.allows("seventy"));
+        assert!(Difficulty::Hard.allows("eighteen"));
+        assert_eq!("hard".parse(), Ok(Difficulty::Hard));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn language_words_test() {
+        for (name, _) in LANGUAGES {
+            let words = language_words(name).unwrap();
+            assert!(!words.is_empty());
+            assert!(validate_words(words).is_ok());
+        }
+        assert_eq!(language_words("english"), Ok(WORDS));
+        let e = language_words("klingon").unwrap_err();
+        assert!(e.contains("\"klingon\""), "{}", e);
+        assert!(e.contains("--list-languages"));
+    }
+
+    #[test]
+    fn read_words_test() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "borrow  checker\nlifetime\n\ntrait").unwrap();
+        let words = read_words(file.path()).unwrap();
+        assert_eq!(words, ["borrow", "checker", "lifetime", "trait"]);
+        let mut generator = Generator {
+            words: words.clone(),
+            ..Default::default()
+        };
+        let line = Line::new(&mut generator);
+        assert!(line
+            .expected
+            .split(' ')
+            .all(|word| words.iter().any(|w| w == word)));
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "don’t it's").unwrap();
+        assert_eq!(read_words(file.path()).unwrap(), ["don't", "it's"]);
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        assert!(read_words(empty.path())
+            .unwrap_err()
+            .contains("has no words"));
+        let missing = Path::new("this/file/does/not/exist.txt");
+        assert!(read_words(missing).unwrap_err().contains("Could not read"));
+    }
+
+    #[test]
+    fn wpm_tiers_test() {
+        let tiers = WpmTiers::default();
+        assert_eq!(tiers.color(0.0), Color::Red);
+        assert_eq!(tiers.color(39.9), Color::Red);
+        assert_eq!(tiers.color(40.0), Color::Yellow);
+        assert_eq!(tiers.color(80.0), Color::Yellow);
+        assert_eq!(tiers.color(80.1), Color::Green);
+        let tiers = WpmTiers {
+            slow: 60.0,
+            fast: 100.0,
+        };
+        assert_eq!(tiers.color(50.0), Color::Red);
+        assert_eq!(tiers.color(90.0), Color::Yellow);
+        assert_eq!(tiers.color(101.0), Color::Green);
+    }
+
+    #[test]
+    fn filter_words_test() {
+        let words = ["a", "bb", "ccccc"].map(String::from);
+        assert_eq!(filter_words(&words, Difficulty::Easy), ["a", "bb"]);
+        assert_eq!(filter_words(&words, Difficulty::Medium), ["ccccc"]);
+        // no words are long enough so all of them are used
+        assert_eq!(filter_words(&words, Difficulty::Hard), words);
+    }
+
+    #[test]
+    fn filter_lengths_test() {
+        let words = ["a", "bb", "ccc", "dddd", "éé"].map(String::from);
+        assert_eq!(filter_lengths(&words, None, None), Ok(words.to_vec()));
+        assert_eq!(
+            filter_lengths(&words, Some(2), Some(3)),
+            Ok(["bb", "ccc", "éé"].map(String::from).to_vec())
+        );
+        assert_eq!(
+            filter_lengths(&words, Some(4), None),
+            Ok(vec!["dddd".to_string()])
+        );
+        assert_eq!(
+            filter_lengths(&words, None, Some(1)),
+            Ok(vec!["a".to_string()])
+        );
+        let e = filter_lengths(&words, Some(5), None).unwrap_err();
+        assert!(e.contains("at least 5"));
+        let e = filter_lengths(&words, Some(3), Some(2)).unwrap_err();
+        assert!(e.contains("between 3 and 2"));
+        assert!(filter_lengths(&[], None, None).is_err());
+    }
+
+    #[test]
+    fn seeded_line_test() {
+        let seeded = |seed| Generator {
+            rng: StdRng::seed_from_u64(seed),
+            punctuation: true,
+            numbers: true,
+            ..Default::default()
+        };
+        let (mut a, mut b) = (seeded(42), seeded(42));
+        for _ in 0..10 {
+            assert_eq!(Line::new(&mut a).expected, Line::new(&mut b).expected);
+        }
+        let mut c = seeded(43);
+        let lines = |generator: &mut Generator| {
+            (0..10)
+                .map(|_| Line::new(generator).expected)
+                .collect::<Vec<_>>()
+        };
+        assert_ne!(lines(&mut a), lines(&mut c));
+    }
+
+    #[test]
+    fn capitalize_test() {
+        assert_eq!(capitalize("word"), "Word");
+        assert_eq!(capitalize("Word"), "Word");
+        assert_eq!(capitalize("élan"), "Élan");
+        assert_eq!(capitalize(""), "");
+    }
+
+    #[test]
+    fn punctuate_test() {
+        let mut generator = Generator {
+            punctuation: true,
+            ..Default::default()
+        };
+        let words: Vec<String> = (0..1000)
+            .map(|_| punctuate("word", &mut generator))
+            .collect();
+        assert!(words[0].starts_with('W'));
+        for pair in words.windows(2) {
+            let ends_sentence = pair[0].ends_with('.') || pair[0].ends_with('?');
+            assert_eq!(pair[1].starts_with('W'), ends_sentence);
+        }
+        let punctuated = words
+            .iter()
+            .filter(|w| w.ends_with(&PUNCTUATION[..]))
+            .count();
+        assert!(
+            (100..300).contains(&punctuated),
+            "{} punctuated",
+            punctuated
+        );
+    }
+
+    #[test]
+    fn line_punctuation_word_count_test() {
+        let mut generator = Generator {
+            punctuation: true,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let mut line = Line::new(&mut generator);
+            line.buffer = line.expected.clone();
+            assert_eq!(line.word_count() as usize, LINE_LEN);
+        }
+    }
+
+    #[test]
+    fn no_repeats_test() {
+        let mut generator = Generator {
+            no_repeats: true,
+            words: WORDS[..20].iter().map(|s| s.to_string()).collect(),
+            rng: StdRng::seed_from_u64(0),
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let line = next_line(&mut generator);
+            let words: HashSet<&str> = line.split(' ').collect();
+            assert_eq!(words.len(), LINE_LEN, "{}", line);
+        }
+        generator.weighting = Weighting::ByRank;
+        generator.width = Some(60);
+        for _ in 0..100 {
+            let line = next_line(&mut generator);
+            let words: Vec<&str> = line.split(' ').collect();
+            let unique: HashSet<&&str> = words.iter().collect();
+            assert_eq!(unique.len(), words.len(), "{}", line);
+        }
+        // with too few words a word is only repeated when it was not the last one
+        generator.weighting = Weighting::Even;
+        generator.distribution = None;
+        generator.width = None;
+        generator.words = vec!["a".into(), "b".into(), "c".into()];
+        for _ in 0..100 {
+            let line = next_line(&mut generator);
+            let words: Vec<&str> = line.split(' ').collect();
+            assert!(words.windows(2).all(|pair| pair[0] != pair[1]), "{}", line);
+            assert_eq!(words.len(), LINE_LEN);
+        }
+        generator.words = vec!["only".into()];
+        assert_eq!(next_line(&mut generator), join(["only"; LINE_LEN]));
+    }
+
+    #[test]
+    fn draw_word_test() {
+        let strings = |words: &[&str]| words.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut generator = Generator {
+            no_repeats: true,
+            words: strings(&["a", "b", "c", "d"]),
+            weighting: Weighting::ByRank,
+            rng: StdRng::seed_from_u64(0),
+            ..Default::default()
+        };
+        let counts = (0..12000).fold(HashMap::new(), |mut counts, _| {
+            *counts
+                .entry(draw_word(&mut generator, &strings(&["a"])))
+                .or_insert(0) += 1;
+            counts
+        });
+        assert_eq!(counts.get("a"), None);
+        // the words keep their ranks so "b" is still about twice as likely as "d"
+        let ratio = counts["b"] as f32 / counts["d"] as f32;
+        assert!((1.7..2.3).contains(&ratio), "{:?}", counts);
+        generator.weighting = Weighting::Even;
+        generator.distribution = None;
+        assert_eq!(draw_word(&mut generator, &strings(&["a", "b", "d"])), "c");
+        let picked = strings(&["a", "c", "b", "d"]);
+        for _ in 0..20 {
+            assert_ne!(draw_word(&mut generator, &picked), "d");
+        }
+        generator.words = strings(&["only"]);
+        assert_eq!(draw_word(&mut generator, &strings(&["only"])), "only");
+    }
+
+    #[test]
+    fn capitalize_ratio_test() {
+        let capitalized = |generator: &mut Generator| {
+            (0..2000)
+                .map(|_| generate_word(generator, &mut Vec::new()))
+                .filter(|word| word.starts_with(char::is_uppercase))
+                .count()
+        };
+        let mut generator = Generator {
+            words: vec!["word".into()],
+            rng: StdRng::seed_from_u64(0),
+            capitalize_ratio: 0.25,
+            ..Generator::default()
+        };
+        let count = capitalized(&mut generator);
+        assert!((400..600).contains(&count), "{} capitalized", count);
+        generator.capitalize_ratio = 0.0;
+        assert_eq!(capitalized(&mut generator), 0);
+        generator.capitalize_ratio = 1.0;
+        assert_eq!(capitalized(&mut generator), 2000);
+    }
+
+    #[test]
+    fn code_line_test() {
+        let mut generator = Generator {
+            code: true,
+            width: Some(30),
+            rng: StdRng::seed_from_u64(0),
+            ..Generator::default()
+        };
+        for _ in 0..100 {
+            let line = Line::new(&mut generator);
+            assert!(CODE.contains(&line.expected()));
+            assert!(line.expected().chars().count() <= 30);
+            // indentation is typed already so only the code is left
+            assert_eq!(line.buffer, indentation(line.expected()));
+            assert_eq!(line.indent, line.buffer.len());
+            assert_eq!(line.char_count(), 0);
+            assert_eq!(line.word_count(), 0);
+            assert_eq!(line.keystrokes(), (0, 0));
+        }
+        generator.width = Some(1);
+        assert!(CODE.contains(&next_code_line(&mut generator).as_str()));
+        assert_eq!(indentation("    let x = 1;"), "    ");
+        assert_eq!(indentation("}"), "");
+    }
+
+    #[test]
+    fn code_word_count_test() {
+        let mut line = Line {
+            buffer: "    ".into(),
+            expected: "    total += item.price;".into(),
+            ..Line::EMPTY
+        };
+        for ch in "total += item.price;".chars() {
+            line.add_char(ch);
+        }
+        assert_eq!(line.word_count(), 3);
+        let line = Line {
+            buffer: "    tota; +=".into(),
+            expected: "    total += item.price;".into(),
+            ..Line::EMPTY
+        };
+        assert_eq!(line.word_count(), 1);
+    }
+
+    #[test]
+    fn code_indent_test() {
+        let mut line = Line {
+            buffer: "    ".into(),
+            expected: "    total += item.price;".into(),
+            indent: 4,
+            ..Line::EMPTY
+        };
+        // the indentation was not typed so it is not counted
+        assert_eq!(line.char_count(), 0);
+        line.add_char('t');
+        assert_eq!(line.char_count(), 1);
+        line.backspace_word();
+        assert_eq!(line.buffer, "    ");
+        line.backspace();
+        assert_eq!(line.buffer, "    ");
+        assert_eq!(line.fixed(), 0);
+    }
+
+    #[test]
+    fn replace_with_number_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let words: Vec<String> = (0..1000)
+            .map(|_| replace_with_number("word", &mut rng))
+            .collect();
+        let numbers: Vec<u32> = words.iter().filter_map(|w| w.parse().ok()).collect();
+        assert!(
+            (100..200).contains(&numbers.len()),
+            "{} numbers",
+            numbers.len()
+        );
+        assert!(numbers.iter().all(|&n| n <= MAX_NUMBER));
+        assert!(words
+            .iter()
+            .all(|w| w == "word" || w.parse::<u32>().is_ok()));
+        let mut rng = StdRng::seed_from_u64(0);
+        let again: Vec<String> = (0..1000)
+            .map(|_| replace_with_number("word", &mut rng))
+            .collect();
+        assert_eq!(words, again);
+    }
+
+    #[test]
+    fn line_numbers_word_count_test() {
+        let line = Line {
+            buffer: "the 42 of 9999 and".into(),
+            expected: "the 42 of 9999 and".into(),
+            ..Line::EMPTY
+        };
+        assert_eq!(line.word_count(), 5);
+        let line = Line {
+            buffer: "the 43 of 9999 and".into(),
+            expected: "the 42 of 9999 and".into(),
+            ..Line::EMPTY
+        };
+        assert_eq!(line.word_count(), 4);
+    }
+
+    #[test]
+    fn next_line_width_test() {
+        for width in [1, 10, 20, 45, 80, 200] {
+            for punctuation in [false, true] {
+                let mut generator = Generator {
+                    width: Some(width),
+                    punctuation,
+                    ..Default::default()
+                };
+                for _ in 0..100 {
+                    let line = next_line(&mut generator);
+                    let words = line.split(' ').count();
+                    assert!(words >= 1);
+                    assert!(
+                        words == 1 || line.chars().count() <= width,
+                        "{:?} is wider than {}",
+                        line,
+                        width
+                    );
+                }
+            }
+        }
+        let mut generator = Generator::default();
+        assert_eq!(next_line(&mut generator).split(' ').count(), LINE_LEN);
+    }
+
+    #[test]
+    fn line_from_quote_test() {
+        let mut s = "This is a quote".to_string();
+        let s_clone = s.clone();
+        let line = Line::from_quote(&mut s, false, None);
+        assert_eq!(s, "");
+        assert_eq!(line.expected, s_clone);
+        let offset = 3;
+        s = join(1..=(LINE_LEN + offset));
+        let line = Line::from_quote(&mut s, false, None);
+        assert_eq!(s, join((LINE_LEN + 1)..=(LINE_LEN + offset)));
+        assert_eq!(line.expected, join(1..=LINE_LEN));
+        // messy whitespace is collapsed before splitting into lines
+        let mut s = crate::quote::normalize("  one\ttwo \n\nthree  ");
+        let line = Line::from_quote(&mut s, false, None);
+        assert_eq!(line.expected, "one two three");
+        assert_eq!(line.expected.split(' ').count(), 3);
+    }
+
+    #[test]
+    fn line_from_quote_width_test() {
+        let quote = "The rain has stopped and the sky is clear so why wait any longer \
+            before we leave extraordinarily early";
+        for width in [1, 5, 10, 20, 45, 80] {
+            let mut s = quote.to_string();
+            let mut lines = Vec::new();
+            while !s.is_empty() {
+                let line = Line::from_quote(&mut s, false, Some(width)).expected;
+                let words = line.split(' ').count();
+                assert!(words >= 1);
+                assert!(
+                    words == 1 || line.chars().count() <= width,
+                    "{:?} is wider than {}",
+                    line,
+                    width
+                );
+                lines.push(line);
+            }
+            assert_eq!(lines.join(" "), quote);
+        }
+        // lines are filled as far as the width allows instead of stopping at {LINE_LEN} words
+        let mut s = join(["a"; 30]);
+        let line = Line::from_quote(&mut s, false, Some(39));
+        assert_eq!(line.expected, join(["a"; 20]));
+        let mut s = "one two. three four five six".to_string();
+        let line = Line::from_quote(&mut s, true, Some(18));
+        assert_eq!(line.expected, "one two. three");
+        assert_eq!(fitting_words(&["wide", "日本"], 9), 2);
+        assert_eq!(fitting_words(&["wide", "日本"], 8), 1);
+        assert_eq!(fitting_words(&["extraordinarily"], 5), 1);
+        assert_eq!(fitting_words(&[], 5), 0);
+    }
+
+    #[test]
+    fn line_from_quote_sentences_test() {
+        let mut s = "The rain has stopped and the sky is clear! Why wait any longer? \
+            We should leave before it starts again. It could be soon."
+            .to_string();
+        let lines: Vec<String> = std::iter::from_fn(|| {
+            (!s.is_empty()).then(|| Line::from_quote(&mut s, true, None).expected)
+        })
+        .collect();
+        assert_eq!(
+            lines,
+            [
+                "The rain has stopped and the sky is clear!",
+                "Why wait any longer?",
+                "We should leave before it starts again.",
+                "It could be soon."
+            ]
+        );
+        // sentences are only split where the line keeps enough words
+        let mut s = "One two three. Four five six seven eight nine ten eleven".to_string();
+        let line = Line::from_quote(&mut s, true, None);
+        assert_eq!(line.expected.split(' ').count(), LINE_LEN);
+        assert_eq!(s, "eleven");
+        // a long sentence is still cut at the word cap
+        let mut s = join(1..=(2 * LINE_LEN)) + ".";
+        let line = Line::from_quote(&mut s, true, None);
+        assert_eq!(line.expected, join(1..=LINE_LEN));
+        let mut s =
+            "\"Is it over?\" she said. Then she left the room and closed the door.".to_string();
+        assert_eq!(
+            Line::from_quote(&mut s, true, None).expected,
+            "\"Is it over?\" she said."
+        );
+        assert!(ends_sentence("done.\""));
+        assert!(ends_sentence("(really!)"));
+        assert!(!ends_sentence("however,"));
+    }
+
+    #[test]
+    fn plain_quotes_test() {
+        assert_eq!(plain_quotes("“It’s ‘fine’”"), "\"It's 'fine'\"");
+        assert_eq!(plain_quotes("plain 'text'"), "plain 'text'");
+        let mut s = "“Don’t panic.”".to_string();
+        let mut line = Line::from_quote(&mut s, false, None);
+        for ch in "\"Don't panic.\"".chars() {
+            line.add_char(ch);
+        }
+        assert_eq!(line.word_count(), 2);
+        assert_eq!(line.keystrokes(), (14, 0));
+    }
+
+    #[test]
+    fn line_empty_test() {
+        let line = Line::EMPTY;
+        assert_eq!(line.buffer, "");
+        assert_eq!(line.expected, "");
+    }
+
+    #[test]
+    fn line_index_test() {
+        let mut line = Line::new(&mut Generator::default());
+        line.buffer = "abc 12".to_string();
+        assert_eq!(line.index(), 6);
+        line.buffer = "123".to_string();
+        assert_eq!(line.index(), 3);
+        line.buffer = "This one is pretty long".to_string();
+        assert_eq!(line.index(), 23);
+        line.buffer = "café".to_string();
+        assert_eq!(line.index(), 4);
+        // an e followed by a combining accent
+        line.buffer = "cafe\u{301}".to_string();
+        assert_eq!(line.index(), 4);
+        line.buffer = "hi 👋".to_string();
+        assert_eq!(line.index(), 5);
+        line.buffer = "👨\u{200d}👩\u{200d}👧".to_string();
+        assert_eq!(line.index(), 2);
+    }
+
+    #[test]
+    fn wide_characters_test() {
+        let theme = Theme::default();
+        let mut s = "日本語 テスト".to_string();
+        let mut line = Line::from_quote(&mut s, false, None);
+        assert_eq!(line.drawn_width(), 13);
+        for ch in "日本語 ".chars() {
+            line.add_char(ch);
+        }
+        assert_eq!(line.index(), 7);
+        assert_eq!(line.column(80), 7);
+        assert_eq!(line.drawn_width(), 13);
+        // wide characters are cut off by the columns they take up
+        let chars = line.styled_chars(8, &theme, false, true);
+        let glyphs: String = chars.iter().map(|ch| *ch.content()).collect();
+        assert_eq!(glyphs, "日本語 …");
+        assert_eq!(display_width(&glyphs), 8);
+        let chars = line.styled_chars(13, &theme, false, true);
+        assert_eq!(chars.len(), 7);
+    }
+
+    #[test]
+    fn display_width_test() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("naïve"), 5);
+        assert_eq!(display_width("🎉🎉"), 4);
+    }
+
+    #[test]
+    fn line_word_count_test() {
+        for (b, e, count) in [
+            ("a b d", "a b c d", 2),
+            ("a b c", "a b c d", 3),
+            ("This is a quote!", "This is a quote!", 4),
+            ("This is not a quote!", "This is a quote!", 2),
+        ] {
             let line = Line {
                 buffer: b.into(),
                 expected: e.into(),
+                ..Line::EMPTY
             };
             assert_eq!(line.word_count(), count);
         }
     }
 
+    #[test]
+    fn contraction_test() {
+        let line = |buffer: &str| Line {
+            buffer: buffer.into(),
+            expected: "don't stop we'll see".into(),
+            ..Line::EMPTY
+        };
+        // each contraction is a single word that only counts with the apostrophe typed
+        assert_eq!(line("don't stop we'll see").word_count(), 4);
+        assert_eq!(line("don't stop we'll see").raw_word_count(), 4);
+        assert_eq!(line("don't").word_count(), 1);
+        assert_eq!(line("don'").word_count(), 0);
+        assert_eq!(line("dont").word_count(), 0);
+        assert_eq!(line("don`t stop").word_count(), 1);
+        assert_eq!(line("don’t stop welll see").word_count(), 2);
+        let line = line("don`t");
+        assert_eq!(line.current_word(), ("don't", "don`t"));
+        let chars = line.styled_chars(80, &Theme::default(), false, true);
+        assert_eq!(chars[2], 'n'.with(COMPLETED));
+        assert_eq!(chars[3], '`'.with(ERROR));
+        assert_eq!(chars[4], 't'.with(COMPLETED));
+        let line = Line {
+            buffer: "don't".into(),
+            ..line
+        };
+        let chars = line.styled_chars(80, &Theme::default(), false, true);
+        assert_eq!(chars[3], '\''.with(COMPLETED));
+        assert_eq!(chars[6], 's'.with(UNCOMPLETED));
+    }
+
+    #[test]
+    fn is_clean_test() {
+        let line = |buffer: &str| Line {
+            buffer: buffer.into(),
+            expected: "the quick fox".into(),
+            ..Line::EMPTY
+        };
+        assert!(line("").is_clean());
+        assert!(line("the qu").is_clean());
+        assert!(line("the quick fox").is_clean());
+        assert!(!line("teh").is_clean());
+        assert!(!line("the quick fox!").is_clean());
+    }
+
+    #[test]
+    fn word_marks_test() {
+        let line = |buffer: &str| Line {
+            buffer: buffer.into(),
+            expected: "the quick  brown fox".into(),
+            ..Line::EMPTY
+        };
+        assert!(line("").word_marks().is_empty());
+        assert_eq!(line("th").word_marks(), [("the", false)]);
+        assert_eq!(line("the").word_marks(), [("the", true)]);
+        assert_eq!(
+            line("teh quick  b").word_marks(),
+            [("the", false), ("quick", true), ("brown", false)]
+        );
+        // an extra character shifts the rest of the line out of place
+        assert_eq!(
+            line("the quicck  brown fox").word_marks(),
+            [
+                ("the", true),
+                ("quick", false),
+                ("brown", false),
+                ("fox", false)
+            ]
+        );
+        assert_eq!(
+            line("the quick  brown fox").word_marks(),
+            [
+                ("the", true),
+                ("quick", true),
+                ("brown", true),
+                ("fox", true)
+            ]
+        );
+        for buffer in ["th", "the quick  brown fox", "teh quick  b", "x"] {
+            let line = line(buffer);
+            let correct = line.word_marks().iter().filter(|(_, ok)| *ok).count();
+            assert_eq!(correct as u32, line.word_count(), "{}", buffer);
+        }
+    }
+
+    #[test]
+    fn line_char_count_test() {
+        for (b, count) in [
+            ("", 0),
+            ("a", 1),
+            ("a b", 3),
+            ("a bx", 3),
+            ("x bc", 3),
+            ("a bc d", 6),
+            // characters are compared by position
+            ("ab c d", 4),
+            ("a bc dxyz", 6),
+        ] {
+            let line = Line {
+                buffer: b.into(),
+                expected: "a bc d".into(),
+                ..Line::EMPTY
+            };
+            assert_eq!(line.char_count(), count, "{:?}", b);
+        }
+    }
+
+    #[test]
+    fn line_raw_word_count_test() {
+        for (b, e, net, raw) in [
+            ("", "a b c d", 0, 0),
+            ("a b", "a b c d", 2, 2),
+            ("a b c", "a b cat d", 2, 3),
+            ("a b ", "a b c d", 2, 2),
+            ("x y z ", "a b c d", 0, 3),
+            ("a y c d", "a b c d", 3, 4),
+            ("x y z w", "a b c d", 0, 4),
+            ("a  b ", "a b c d", 1, 2),
+        ] {
+            let line = Line {
+                buffer: b.into(),
+                expected: e.into(),
+                ..Line::EMPTY
+            };
+            assert_eq!(line.word_count(), net, "{:?}", b);
+            assert_eq!(line.raw_word_count(), raw, "{:?}", b);
+        }
+    }
+
+    #[test]
+    fn line_current_word_test() {
+        for (b, e, current) in [
+            ("", "a bc d", ("a", "")),
+            ("a b", "a bc d", ("bc", "b")),
+            ("a bc", "a bc d", ("bc", "bc")),
+            ("a bc ", "a bc d", ("d", "")),
+            ("a bx", "a bc d", ("bc", "bx")),
+            ("a bc d ", "a bc d", ("", "")),
+        ] {
+            let line = Line {
+                buffer: b.into(),
+                expected: e.into(),
+                ..Line::EMPTY
+            };
+            assert_eq!(line.current_word(), current);
+        }
+    }
+
+    #[test]
+    fn line_current_word_correct_test() {
+        for (b, correct) in [
+            ("", false),
+            ("a", true),
+            ("a b", false),
+            ("a bc", true),
+            ("a bx", false),
+            ("a bcd", false),
+            ("a bc d", true),
+        ] {
+            let line = Line {
+                buffer: b.into(),
+                expected: "a bc d".into(),
+                ..Line::EMPTY
+            };
+            assert_eq!(line.current_word_correct(), correct, "{:?}", b);
+        }
+    }
+
+    #[test]
+    fn line_current_word_bounds_test() {
+        for (b, bounds) in [
+            ("", Some(0..1)),
+            ("a", Some(0..1)),
+            ("a ", Some(2..4)),
+            ("a b", Some(2..4)),
+            ("a xy", Some(2..4)),
+            ("a bc ", Some(5..6)),
+            ("a bc d", None),
+            ("a bc d e", None),
+        ] {
+            let line = Line {
+                buffer: b.into(),
+                expected: "a bc d".into(),
+                ..Line::EMPTY
+            };
+            assert_eq!(line.current_word_bounds(), bounds, "{:?}", b);
+        }
+        let line = Line {
+            buffer: "é".into(),
+            expected: "éa bc".into(),
+            ..Line::EMPTY
+        };
+        assert_eq!(line.current_word_bounds(), Some(0..2));
+    }
+
+    #[test]
+    fn line_next_expected_test() {
+        let mut line = Line {
+            buffer: String::new(),
+            expected: "ab".into(),
+            ..Line::EMPTY
+        };
+        assert_eq!(line.next_expected(), Some('a'));
+        line.add_char('x');
+        assert_eq!(line.next_expected(), Some('b'));
+        line.add_char('b');
+        assert_eq!(line.next_expected(), None);
+    }
+
+    #[test]
+    fn line_is_mistake_test() {
+        let mut line = Line {
+            buffer: String::new(),
+            expected: "ab".into(),
+            ..Line::EMPTY
+        };
+        assert!(!line.is_mistake('a'));
+        assert!(line.is_mistake('b'));
+        assert!(line.is_mistake(' '));
+        line.add_char('x');
+        assert!(!line.is_mistake('b'));
+        line.add_char('b');
+        assert!(!line.is_mistake('x'));
+    }
+
     #[test]
     fn line_backspace_test() {
         let mut line = Line::EMPTY;
         line.backspace(); // shouldn't panic
-        let mut line = Line::new();
+        let mut line = Line::new(&mut Generator::default());
         line.buffer = "abc".to_string();
         for _ in 0..3 {
             line.backspace();
@@ -238,15 +2100,296 @@ mod tests {
         assert_eq!(line.buffer.len(), 0);
     }
 
+    #[test]
+    fn line_backspace_word_test() {
+        let mut line = Line::EMPTY;
+        line.backspace_word(); // shouldn't panic
+        assert_eq!(line.buffer, "");
+        let mut line = Line::new(&mut Generator::default());
+        line.buffer = "hello wor".to_string();
+        line.backspace_word();
+        assert_eq!(line.buffer, "hello ");
+        line.backspace_word();
+        assert_eq!(line.buffer, "");
+        line.buffer = "one two  ".to_string();
+        line.backspace_word();
+        assert_eq!(line.buffer, "one ");
+    }
+
     #[test]
     fn line_add_char_test() {
-        let mut line = Line::new();
+        let mut line = Line::new(&mut Generator::default());
         line.add_char('1');
         line.add_char('2');
         line.add_char('3');
         assert_eq!(line.buffer.len(), 3);
     }
 
+    #[test]
+    fn line_add_char_past_end_test() {
+        let mut line = Line {
+            expected: "ab".into(),
+            ..Line::EMPTY
+        };
+        assert!(line.add_char('a'));
+        assert!(line.add_char('x'));
+        for ch in "cd ".chars() {
+            assert!(!line.add_char(ch));
+        }
+        assert_eq!(line.buffer, "ax");
+        assert_eq!(line.index(), line.expected.len());
+        assert_eq!(line.keystrokes(), (2, 1));
+        let mut line = Line {
+            expected: "café".into(),
+            ..Line::EMPTY
+        };
+        for ch in "cafés".chars() {
+            line.add_char(ch);
+        }
+        assert_eq!(line.buffer, "café");
+        assert!(line.done());
+    }
+
+    #[test]
+    fn parse_color_test() {
+        assert_eq!(parse_color("#e60000"), Ok(ERROR));
+        assert_eq!(parse_color("#FFffFF"), Ok(gray(255)));
+        assert_eq!(parse_color("red"), Ok(Color::Red));
+        assert_eq!(parse_color("Dark_Grey"), Ok(Color::DarkGrey));
+        for invalid in ["", "#", "#e6000", "#e600000", "#gggggg", "#ééé", "reddish"] {
+            let e = parse_color(invalid).unwrap_err();
+            assert!(e.contains(&format!("\"{invalid}\"")), "{}", e);
+            assert!(e.contains("#e60000"));
+        }
+    }
+
+    #[test]
+    fn named_theme_test() {
+        assert_eq!(THEMES.len(), 4);
+        for (name, theme) in THEMES {
+            assert_eq!(Theme::named(name), Ok(*theme));
+            let colors = [theme.completed, theme.uncompleted, theme.error];
+            for color in colors {
+                assert!(matches!(color, Color::Rgb { .. }), "{name}");
+            }
+            assert_ne!(colors[0], colors[1], "{name}");
+            assert_ne!(colors[0], colors[2], "{name}");
+            assert_ne!(colors[1], colors[2], "{name}");
+            assert!(theme.labels.is_some(), "{name}");
+        }
+        let names: Vec<&str> = THEMES.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["dark", "light", "solarized", "monokai"]);
+        let e = Theme::named("neon").unwrap_err();
+        assert!(e.contains("\"neon\""), "{}", e);
+        assert!(e.contains("solarized"), "{}", e);
+        assert_eq!(Theme::default().label_color(Color::Red), Color::Red);
+        assert_eq!(
+            Theme::named("light").unwrap().label_color(Color::Red),
+            Color::Rgb {
+                r: 0,
+                g: 90,
+                b: 180
+            }
+        );
+    }
+
+    #[test]
+    fn line_styled_chars_test() {
+        let line = Line {
+            buffer: "abd".into(),
+            expected: "abc de".into(),
+            ..Line::EMPTY
+        };
+        let theme = Theme::default();
+        let chars = line.styled_chars(80, &theme, false, true);
+        assert_eq!(chars.len(), 6);
+        assert_eq!(chars[0], 'a'.with(COMPLETED));
+        assert_eq!(chars[2], 'd'.with(ERROR));
+        assert_eq!(chars[3], ' '.with(UNCOMPLETED));
+        assert_eq!(line.styled_chars(0, &theme, false, true).len(), 0);
+        let theme = Theme {
+            error: Color::Blue,
+            ..theme
+        };
+        assert_eq!(
+            line.styled_chars(80, &theme, false, true)[2],
+            'd'.with(Color::Blue)
+        );
+        // the rest of the current word is underlined but not the next word
+        let line = Line {
+            buffer: "a".into(),
+            ..line
+        };
+        let chars = line.styled_chars(80, &Theme::default(), true, true);
+        assert_eq!(chars[1], 'b'.with(UNCOMPLETED).underlined());
+        assert_eq!(chars[2], 'c'.with(UNCOMPLETED).underlined());
+        assert_eq!(chars[3], ' '.with(UNCOMPLETED));
+        assert_eq!(chars[4], 'd'.with(UNCOMPLETED));
+    }
+
+    #[test]
+    fn style_typed_test() {
+        let theme = Theme::default();
+        assert_eq!(
+            style_typed('a', Some('a'), &theme, true),
+            'a'.with(COMPLETED)
+        );
+        assert_eq!(style_typed('a', Some('b'), &theme, true), 'a'.with(ERROR));
+        assert_eq!(style_typed(' ', Some('b'), &theme, true), ' '.on(ERROR));
+        assert_eq!(style_typed('a', None, &theme, true), 'a'.with(ERROR));
+        // errors look like correct characters without highlighting
+        assert_eq!(
+            style_typed('a', Some('b'), &theme, false),
+            'a'.with(COMPLETED)
+        );
+        assert_eq!(
+            style_typed(' ', Some('b'), &theme, false),
+            ' '.with(COMPLETED)
+        );
+        let line = Line {
+            buffer: "abd".into(),
+            expected: "abc".into(),
+            ..Line::EMPTY
+        };
+        assert_ne!(
+            line.styled_chars(80, &theme, false, true),
+            line.styled_chars(80, &theme, false, false)
+        );
+    }
+
+    #[test]
+    fn center_padding_test() {
+        assert_eq!(center_padding(10, 80), 35);
+        assert_eq!(center_padding(9, 80), 35);
+        assert_eq!(center_padding(80, 80), 0);
+        assert_eq!(center_padding(100, 80), 0);
+        let line = Line {
+            buffer: "abcdef".into(),
+            expected: "abc".into(),
+            ..Line::EMPTY
+        };
+        assert_eq!(line.drawn_width(), 6);
+    }
+
+    #[test]
+    fn place_caret_test() {
+        let theme = Theme::default();
+        let caret = '|'.with(COMPLETED);
+        let line = Line {
+            buffer: "ab".into(),
+            expected: "abcd".into(),
+            ..Line::EMPTY
+        };
+        let glyphs = |chars: &[StyledContent<char>]| -> String {
+            chars.iter().map(|ch| *ch.content()).collect()
+        };
+        let mut chars = line.styled_chars(80, &theme, true, true);
+        place_caret(&mut chars, line.typed_len(), caret, 80);
+        assert_eq!(glyphs(&chars), "ab|d");
+        // the caret goes at the end once the line is finished
+        let mut chars = line.styled_chars(80, &theme, true, true);
+        place_caret(&mut chars, 4, caret, 80);
+        assert_eq!(glyphs(&chars), "abcd|");
+        // the last column is not cut off
+        let mut chars = line.styled_chars(4, &theme, true, true);
+        place_caret(&mut chars, 0, caret, 4);
+        assert_eq!(glyphs(&chars), "|bcd");
+        let mut chars = line.styled_chars(4, &theme, true, true);
+        place_caret(&mut chars, 4, caret, 4);
+        assert_eq!(glyphs(&chars), "abcd");
+    }
+
+    #[test]
+    fn mark_pace_test() {
+        let theme = Theme::default();
+        let line = Line {
+            buffer: "a ".into(),
+            expected: "abc".into(),
+            ..Line::EMPTY
+        };
+        let chars = line.styled_chars(80, &theme, false, true);
+        let mut marked = chars.clone();
+        mark_pace(&mut marked, Some(2));
+        assert_eq!(marked[2], 'c'.with(UNCOMPLETED).on(PACE));
+        assert_eq!(marked[..2], chars[..2]);
+        // the background of an incorrect space is kept
+        mark_pace(&mut marked, Some(1));
+        assert_eq!(marked[1], ' '.on(ERROR));
+        mark_pace(&mut marked, Some(10));
+        mark_pace(&mut marked, None);
+        assert_eq!(marked.len(), 3);
+    }
+
+    #[test]
+    fn line_draw_overflow_test() {
+        let width = 20;
+        let line = Line {
+            buffer: "abc".to_string() + &"x".repeat(50),
+            expected: "abc".into(),
+            ..Line::EMPTY
+        };
+        let chars = line.styled_chars(width, &Theme::default(), false, true);
+        assert_eq!(chars.len(), width);
+        assert_eq!(chars[width - 1], OVERFLOW.with(ERROR));
+        assert_eq!(line.column(width), width - 1);
+        let mut out = Vec::new();
+        line.draw(&mut out, width, &Theme::default(), true, None)
+            .unwrap();
+        let drawn = String::from_utf8(out).unwrap();
+        assert_eq!(drawn.matches('x').count(), width - 4);
+        assert!(drawn.contains(OVERFLOW));
+    }
+
+    #[test]
+    fn line_keystrokes_test() {
+        let mut line = Line {
+            expected: "abc de".into(),
+            ..Line::EMPTY
+        };
+        for ch in "abx".chars() {
+            line.add_char(ch);
+        }
+        assert_eq!(line.keystrokes(), (3, 1));
+        line.backspace();
+        line.add_char('c');
+        line.add_char('-');
+        assert_eq!(line.keystrokes(), (5, 2));
+        // characters past the end of the line are not counted
+        for ch in " de extra".chars() {
+            line.add_char(ch);
+        }
+        assert_eq!(line.keystrokes(), (7, 4));
+    }
+
+    #[test]
+    fn line_fixed_test() {
+        let mut line = Line {
+            expected: "abc déf".into(),
+            ..Line::EMPTY
+        };
+        for ch in "abx".chars() {
+            line.add_char(ch);
+        }
+        // removing a correct character fixes nothing
+        line.backspace();
+        assert_eq!(line.fixed(), 1);
+        line.backspace();
+        assert_eq!(line.fixed(), 1);
+        assert_eq!(line.buffer, "a");
+        for ch in "bc dxx".chars() {
+            line.add_char(ch);
+        }
+        line.backspace_word();
+        assert_eq!(line.buffer, "abc ");
+        assert_eq!(line.fixed(), 3);
+        line.backspace();
+        line.backspace();
+        assert_eq!(line.buffer, "ab");
+        assert_eq!(line.fixed(), 3);
+        assert_eq!(line.keystrokes(), (9, 3));
+        Line::EMPTY.clone().backspace();
+    }
+
     #[test]
     fn line_done_test() {
         for (b, e, done) in [
@@ -260,6 +2403,7 @@ mod tests {
             let line = Line {
                 buffer: b.into(),
                 expected: e.into(),
+                ..Line::EMPTY
             };
             assert_eq!(line.done(), done);
         }
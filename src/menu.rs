@@ -0,0 +1,370 @@
+//! Contains the menu used to choose a test mode before starting a test
+use crate::{Args, InputSource};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use crossterm::{
+    cursor, queue,
+    style::{Print, Stylize},
+    terminal,
+};
+use std::io::prelude::*;
+
+/// The word counts that can be chosen in word mode
+const WORD_COUNTS: &[u32] = &[10, 30, 50, 100];
+/// The number of seconds that can be chosen in time mode
+const TIME_LIMITS: &[u64] = &[15, 30, 60, 120];
+/// The modes in the order they are cycled through
+const MODES: &[Mode] = &[Mode::Words, Mode::Time, Mode::Quote, Mode::Zen];
+
+/// A key press that does something in the menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Start the test with the chosen mode
+    Confirm,
+    /// Exit without starting a test
+    Cancel,
+}
+
+impl MenuKey {
+    /// Get the menu key a terminal event presses if there is one
+    pub fn from_event(event: Event) -> Option<Self> {
+        match event {
+            Event::Key(key) => match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(MenuKey::Cancel)
+                }
+                KeyCode::Up | KeyCode::Char('k') => Some(MenuKey::Up),
+                KeyCode::Down | KeyCode::Char('j') => Some(MenuKey::Down),
+                KeyCode::Left | KeyCode::Char('h') => Some(MenuKey::Left),
+                KeyCode::Right | KeyCode::Char('l') => Some(MenuKey::Right),
+                KeyCode::Enter => Some(MenuKey::Confirm),
+                KeyCode::Esc => Some(MenuKey::Cancel),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The kinds of test that can be chosen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Words,
+    Time,
+    Quote,
+    Zen,
+}
+
+/// A row of the menu that can be selected
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Row {
+    Mode,
+    /// The word count or time limit of the chosen mode
+    Amount,
+    Start,
+}
+
+/// Whether the menu is still waiting for a choice
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum State {
+    Choosing,
+    Started,
+    Cancelled,
+}
+
+/// The selection state of the menu
+#[derive(Debug)]
+pub struct Menu {
+    row: Row,
+    mode: usize,
+    words: usize,
+    time: usize,
+    state: State,
+}
+
+impl Default for Menu {
+    /// Start on 30 words, the same as running without any flags
+    fn default() -> Self {
+        Self {
+            row: Row::Mode,
+            mode: 0,
+            words: 1,
+            time: 1,
+            state: State::Choosing,
+        }
+    }
+}
+
+/// Move an index one step forward or back, wrapping around at either end
+fn step(index: usize, len: usize, forward: bool) -> usize {
+    if forward {
+        (index + 1) % len
+    } else {
+        (index + len - 1) % len
+    }
+}
+
+impl Menu {
+    /// Get the chosen kind of test
+    pub fn mode(&self) -> Mode {
+        MODES[self.mode]
+    }
+
+    /// Get the rows that can be selected for the chosen mode
+    fn rows(&self) -> &'static [Row] {
+        match self.mode() {
+            Mode::Words | Mode::Time => &[Row::Mode, Row::Amount, Row::Start],
+            Mode::Quote | Mode::Zen => &[Row::Mode, Row::Start],
+        }
+    }
+
+    /// Describe the word count or time limit of the chosen mode if it has one
+    pub fn amount(&self) -> Option<String> {
+        match self.mode() {
+            Mode::Words => Some(format!("{} words", WORD_COUNTS[self.words])),
+            Mode::Time => Some(format!("{} seconds", TIME_LIMITS[self.time])),
+            Mode::Quote | Mode::Zen => None,
+        }
+    }
+
+    /// Update the selection after a key press
+    pub fn handle(&mut self, key: MenuKey) {
+        let rows = self.rows();
+        let row = rows.iter().position(|row| *row == self.row).unwrap_or(0);
+        match key {
+            MenuKey::Up => self.row = rows[step(row, rows.len(), false)],
+            MenuKey::Down => self.row = rows[step(row, rows.len(), true)],
+            MenuKey::Left | MenuKey::Right => {
+                let forward = key == MenuKey::Right;
+                match (self.row, self.mode()) {
+                    (Row::Mode, _) => self.mode = step(self.mode, MODES.len(), forward),
+                    (Row::Amount, Mode::Words) => {
+                        self.words = step(self.words, WORD_COUNTS.len(), forward)
+                    }
+                    (Row::Amount, Mode::Time) => {
+                        self.time = step(self.time, TIME_LIMITS.len(), forward)
+                    }
+                    _ => (),
+                }
+            }
+            MenuKey::Confirm => self.state = State::Started,
+            MenuKey::Cancel => self.state = State::Cancelled,
+        }
+    }
+
+    /// Get the text of each row, marking the selected one
+    pub fn lines(&self) -> Vec<(bool, String)> {
+        let mode = format!("{:?}", self.mode()).to_lowercase();
+        self.rows()
+            .iter()
+            .map(|row| {
+                let text = match row {
+                    Row::Mode => format!("Mode: < {} >", mode),
+                    Row::Amount => format!("Length: < {} >", self.amount().unwrap_or_default()),
+                    Row::Start => "Start".to_string(),
+                };
+                (*row == self.row, text)
+            })
+            .collect()
+    }
+
+    /// Set the test mode in the arguments to the chosen one
+    pub fn apply(&self, args: &mut Args) {
+        let mode = self.mode();
+        args.number = (mode == Mode::Words).then(|| WORD_COUNTS[self.words]);
+        args.time = (mode == Mode::Time).then(|| TIME_LIMITS[self.time]);
+        args.quote = mode == Mode::Quote;
+        args.zen = mode == Mode::Zen;
+    }
+
+    /// Draw the menu at the top of the screen
+    fn draw(&self, stdout: &mut impl Write) -> crossterm::Result<()> {
+        queue!(
+            stdout,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0),
+            Print("Choose a test".bold()),
+            cursor::MoveToNextLine(2),
+        )?;
+        for (selected, text) in self.lines() {
+            if selected {
+                queue!(stdout, Print(format!("> {}", text).bold()))?;
+            } else {
+                queue!(stdout, Print(format!("  {}", text)))?;
+            }
+            queue!(stdout, cursor::MoveToNextLine(1))?;
+        }
+        queue!(
+            stdout,
+            cursor::MoveToNextLine(1),
+            Print("Arrow keys - Choose  Enter - Start  Esc - Exit".dim()),
+        )?;
+        stdout.flush()
+    }
+
+    /// Draw the menu and handle input until a test is started or the menu is exited
+    pub fn run(
+        &mut self,
+        input: &mut impl InputSource,
+        stdout: &mut impl Write,
+    ) -> crossterm::Result<State> {
+        self.draw(stdout)?;
+        while self.state == State::Choosing {
+            let key = match input.poll()? {
+                Some(event) => MenuKey::from_event(event),
+                None => continue,
+            };
+            if let Some(key) = key {
+                self.handle(key);
+                self.draw(stdout)?;
+            }
+        }
+        queue!(
+            stdout,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        stdout.flush()?;
+        Ok(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptedInput;
+    use crossterm::event::KeyEvent;
+    use structopt::StructOpt;
+
+    /// Make a menu that has had each key pressed in order
+    fn pressed(keys: &[MenuKey]) -> Menu {
+        let mut menu = Menu::default();
+        for key in keys {
+            menu.handle(*key);
+        }
+        menu
+    }
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn menu_key_from_event_test() {
+        assert_eq!(MenuKey::from_event(key(KeyCode::Up)), Some(MenuKey::Up));
+        assert_eq!(
+            MenuKey::from_event(key(KeyCode::Char('l'))),
+            Some(MenuKey::Right)
+        );
+        assert_eq!(
+            MenuKey::from_event(key(KeyCode::Enter)),
+            Some(MenuKey::Confirm)
+        );
+        assert_eq!(
+            MenuKey::from_event(key(KeyCode::Esc)),
+            Some(MenuKey::Cancel)
+        );
+        assert_eq!(MenuKey::from_event(key(KeyCode::Char('x'))), None);
+        assert_eq!(
+            MenuKey::from_event(Event::Key(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL
+            ))),
+            Some(MenuKey::Cancel)
+        );
+        assert_eq!(MenuKey::from_event(Event::Resize(80, 24)), None);
+    }
+
+    #[test]
+    fn menu_select_mode_test() {
+        let menu = Menu::default();
+        assert_eq!(menu.mode(), Mode::Words);
+        assert_eq!(menu.amount(), Some("30 words".into()));
+        assert_eq!(menu.state, State::Choosing);
+        let menu = pressed(&[MenuKey::Right]);
+        assert_eq!(menu.mode(), Mode::Time);
+        assert_eq!(menu.amount(), Some("30 seconds".into()));
+        // changing the mode wraps around at either end
+        assert_eq!(pressed(&[MenuKey::Left]).mode(), Mode::Zen);
+        assert_eq!(pressed(&[MenuKey::Right; 4]).mode(), Mode::Words);
+        assert_eq!(pressed(&[MenuKey::Right; 2]).amount(), None);
+    }
+
+    #[test]
+    fn menu_select_row_test() {
+        let menu = pressed(&[MenuKey::Down]);
+        assert_eq!(menu.row, Row::Amount);
+        let menu = pressed(&[MenuKey::Down, MenuKey::Right, MenuKey::Right]);
+        assert_eq!(menu.mode(), Mode::Words);
+        assert_eq!(menu.amount(), Some("100 words".into()));
+        assert_eq!(pressed(&[MenuKey::Up]).row, Row::Start);
+        assert_eq!(pressed(&[MenuKey::Down; 3]).row, Row::Mode);
+        // quote mode has no length to choose so moving down goes straight to start
+        let menu = pressed(&[MenuKey::Right, MenuKey::Right, MenuKey::Down]);
+        assert_eq!(menu.row, Row::Start);
+        // the start row ignores left and right
+        let menu = pressed(&[MenuKey::Up, MenuKey::Right]);
+        assert_eq!(menu.mode(), Mode::Words);
+        assert_eq!(menu.amount(), Some("30 words".into()));
+    }
+
+    #[test]
+    fn menu_lines_test() {
+        let menu = pressed(&[MenuKey::Right, MenuKey::Down, MenuKey::Left]);
+        assert_eq!(
+            menu.lines(),
+            [
+                (false, "Mode: < time >".to_string()),
+                (true, "Length: < 15 seconds >".to_string()),
+                (false, "Start".to_string()),
+            ]
+        );
+        let menu = pressed(&[MenuKey::Left]);
+        assert_eq!(menu.lines().len(), 2);
+        assert_eq!(menu.lines()[0], (true, "Mode: < zen >".to_string()));
+    }
+
+    #[test]
+    fn menu_apply_test() {
+        let mut args = Args::from_iter(["typing_test", "--menu"]);
+        pressed(&[MenuKey::Down, MenuKey::Right, MenuKey::Confirm]).apply(&mut args);
+        assert_eq!(args.number, Some(50));
+        assert_eq!(args.time, None);
+        pressed(&[MenuKey::Right, MenuKey::Down, MenuKey::Right]).apply(&mut args);
+        assert_eq!(args.number, None);
+        assert_eq!(args.time, Some(60));
+        assert!(!args.quote);
+        pressed(&[MenuKey::Left]).apply(&mut args);
+        assert_eq!(args.time, None);
+        assert!(args.zen);
+        pressed(&[MenuKey::Left, MenuKey::Left]).apply(&mut args);
+        assert!(args.quote);
+        assert!(!args.zen);
+    }
+
+    #[test]
+    fn menu_run_test() {
+        let mut input = ScriptedInput::new([
+            key(KeyCode::Right),
+            key(KeyCode::Char('x')),
+            key(KeyCode::Down),
+            key(KeyCode::Right),
+            key(KeyCode::Enter),
+        ]);
+        let mut menu = Menu::default();
+        let mut drawn = Vec::new();
+        assert_eq!(menu.run(&mut input, &mut drawn).unwrap(), State::Started);
+        assert_eq!(menu.amount(), Some("60 seconds".into()));
+        assert!(String::from_utf8(drawn).unwrap().contains("60 seconds"));
+        // running out of input presses Esc
+        let mut menu = Menu::default();
+        let mut input = ScriptedInput::new([key(KeyCode::Down)]);
+        assert_eq!(
+            menu.run(&mut input, &mut Vec::new()).unwrap(),
+            State::Cancelled
+        );
+    }
+}
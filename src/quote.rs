@@ -1,37 +1,457 @@
-//! Used to get quotes from <https://api.quotable.io/random>
+//! Used to get quotes from <https://api.quotable.io/random> or another quote API
+use reqwest::blocking::Client;
 use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Where quotes are fetched from by default
+pub const QUOTE_URL: &str = "https://api.quotable.io/random";
+
+/// The number of times a failed request is tried again
+const RETRIES: u32 = 2;
+
+/// How long to wait before the first retry, doubling for each retry after it
+const BACKOFF: Duration = Duration::from_millis(200);
 
 /// Holds response from <https://api.quotable.io/random>
+/// Other APIs that call the quote "text" are accepted too
 #[derive(Deserialize)]
 struct Response {
+    #[serde(alias = "text")]
     content: String,
 }
 
-/// Use reqwest to get quotes from <https://api.quotable.io/random>
-pub fn random_quote() -> String {
-    let err_prefix = "Could not get quote because";
-    let url = "https://api.quotable.io/random";
-    reqwest::blocking::get(url)
-        .unwrap_or_else(|_| {
-            eprintln!("{err_prefix} the url \"{url}\" cannot be fetched.");
-            std::process::exit(1);
-        })
+/// The ways fetching a quote can fail
+#[derive(Debug, PartialEq)]
+pub enum QuoteError {
+    /// The url could not be fetched
+    Network(String),
+    /// The url responded with an unsuccessful status code
+    Status(String, u16),
+    /// The url returned something other than a quote
+    Deserialize(String),
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let err_prefix = "Could not get quote because";
+        match self {
+            QuoteError::Network(url) => {
+                write!(
+                    formatter,
+                    "{err_prefix} the url \"{url}\" cannot be fetched."
+                )
+            }
+            QuoteError::Status(url, status) => write!(
+                formatter,
+                "{err_prefix} the url \"{url}\" responded with status {status}."
+            ),
+            QuoteError::Deserialize(url) => write!(
+                formatter,
+                "{err_prefix} the url \"{url}\" returned an unexpected result."
+            ),
+        }
+    }
+}
+
+impl QuoteError {
+    /// Whether trying again could succeed
+    fn is_temporary(&self) -> bool {
+        match self {
+            QuoteError::Network(_) => true,
+            QuoteError::Status(_, status) => *status >= 500,
+            QuoteError::Deserialize(_) => false,
+        }
+    }
+}
+
+/// Use reqwest to get a quote from a url
+/// Error pages are reported by their status instead of being parsed
+fn fetch_quote(client: &Client, url: &str) -> Result<String, QuoteError> {
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|_| QuoteError::Network(url.into()))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(QuoteError::Status(url.into(), status.as_u16()));
+    }
+    response
         .json::<Response>()
-        .unwrap_or_else(|_| {
-            eprintln!("{err_prefix} the url \"{url}\" returned an unexpected result.");
-            std::process::exit(1);
-        })
-        .content
+        .map(|response| normalize(&response.content))
+        .map_err(|_| QuoteError::Deserialize(url.into()))
+}
+
+/// Fetch a quote, trying again with a growing delay if the problem may be temporary
+/// Each request gives up after {timeout}
+fn fetch_with_retries(url: &str, timeout: Duration) -> Result<String, QuoteError> {
+    let client = Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|_| QuoteError::Network(url.into()))?;
+    let mut delay = BACKOFF;
+    let mut result = fetch_quote(&client, url);
+    for _ in 0..RETRIES {
+        match &result {
+            Err(e) if e.is_temporary() => {
+                thread::sleep(delay);
+                delay *= 2;
+                result = fetch_quote(&client, url);
+            }
+            _ => break,
+        }
+    }
+    result
+}
+
+/// Where quotes come from when one is not given
+#[derive(Clone, Debug)]
+pub struct QuoteSource {
+    /// The API quotes are fetched from
+    pub url: String,
+    /// How long to wait for each request
+    pub timeout: Duration,
+    /// Use bundled quotes without fetching
+    pub offline: bool,
+}
+
+impl QuoteSource {
+    /// Use reqwest to get a quote from the API at {url}
+    pub fn random_quote(&self) -> Result<String, QuoteError> {
+        fetch_with_retries(&self.url, self.timeout)
+    }
+
+    /// Get a quote from the internet, using a bundled quote if offline or if fetching fails
+    /// {report} is told when fetching starts and how it went
+    pub fn quote_or_fallback(&self, mut report: impl FnMut(FetchStatus)) -> String {
+        if self.offline {
+            return fallback_quote();
+        }
+        report(FetchStatus::Fetching);
+        match self.random_quote() {
+            Ok(quote) => {
+                report(FetchStatus::Fetched);
+                quote
+            }
+            Err(e) => {
+                report(FetchStatus::Failed(&e));
+                fallback_quote()
+            }
+        }
+    }
+}
+
+/// A quote being fetched in the background so it is ready by the time it is needed
+#[derive(Debug)]
+pub struct Prefetch(Option<JoinHandle<Result<String, QuoteError>>>);
+
+impl QuoteSource {
+    /// Start fetching a quote in the background
+    pub fn prefetch(&self) -> Prefetch {
+        if self.offline {
+            return Prefetch(None);
+        }
+        let source = self.clone();
+        Prefetch(Some(thread::spawn(move || source.random_quote())))
+    }
+}
+
+impl Prefetch {
+    /// Whether or not getting the quote would return right away
+    pub fn is_ready(&self) -> bool {
+        self.0.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Wait for the quote, using a bundled quote if offline or if fetching failed
+    /// {report} is told how fetching went
+    pub fn quote_or_fallback(self, mut report: impl FnMut(FetchStatus)) -> String {
+        let handle = match self.0 {
+            Some(handle) => handle,
+            None => return fallback_quote(),
+        };
+        match handle.join() {
+            Ok(Ok(quote)) => {
+                report(FetchStatus::Fetched);
+                quote
+            }
+            Ok(Err(e)) => {
+                report(FetchStatus::Failed(&e));
+                fallback_quote()
+            }
+            // fetching never panics on purpose so a bundled quote is used quietly
+            Err(_) => fallback_quote(),
+        }
+    }
+}
+
+/// How fetching a quote is going
+/// Shown so that waiting on the network does not look like a hang
+#[derive(Debug, PartialEq)]
+pub enum FetchStatus<'a> {
+    Fetching,
+    Fetched,
+    Failed(&'a QuoteError),
+}
+
+impl fmt::Display for FetchStatus<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchStatus::Fetching => write!(formatter, "Fetching quote..."),
+            FetchStatus::Fetched => write!(formatter, "Fetched quote."),
+            FetchStatus::Failed(e) => write!(formatter, "{e} Using a bundled quote instead."),
+        }
+    }
+}
+
+/// Quotes to use when a quote cannot be fetched
+const FALLBACK_QUOTES: &[&str] = &[
+    "The only thing we have to fear is fear itself.",
+    "In the middle of difficulty lies opportunity.",
+    "It does not matter how slowly you go as long as you do not stop.",
+    "Life is what happens when you're busy making other plans.",
+    "The journey of a thousand miles begins with one step.",
+    "That which does not kill us makes us stronger.",
+    "Whatever you are, be a good one.",
+    "Be yourself; everyone else is already taken.",
+    "Simplicity is the ultimate sophistication.",
+    "Well done is better than well said.",
+    "Knowledge is power.",
+    "The best way out is always through.",
+];
+
+/// Get a random quote from the bundled list of quotes
+pub fn fallback_quote() -> String {
+    FALLBACK_QUOTES[rand::random::<usize>() % FALLBACK_QUOTES.len()].to_string()
+}
+
+/// Collapse all whitespace into single spaces so words are split correctly
+pub fn normalize(quote: &str) -> String {
+    quote.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Read a quote from a file
+/// Returns a message describing the problem if the file cannot be read or is empty
+pub fn read_quote(path: &Path) -> Result<String, String> {
+    let path_name = path.display();
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read quote file \"{path_name}\" because {e}."))?;
+    let quote = normalize(&contents);
+    if quote.is_empty() {
+        Err(format!("The quote file \"{path_name}\" is empty."))
+    } else {
+        Ok(quote)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serve a single HTTP response on a local port and return its url
+    fn serve(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            let _ = stream.write_all(response.as_bytes());
+        });
+        url
+    }
+
+    /// Accept connections without ever responding and return the url and how many were made
+    fn serve_nothing() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&connections);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                counter.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let _stream = stream;
+                    std::thread::sleep(Duration::from_secs(5));
+                });
+            }
+        });
+        (url, connections)
+    }
 
     #[test]
     fn random_quote_test() {
+        let source = QuoteSource {
+            url: QUOTE_URL.into(),
+            timeout: Duration::from_secs(5),
+            offline: false,
+        };
         for _ in 0..3 {
-            assert_ne!(random_quote(), "");
+            assert_ne!(source.random_quote().unwrap(), "");
         }
     }
+
+    #[test]
+    fn fetch_with_retries_test() {
+        let (url, connections) = serve_nothing();
+        let result = fetch_with_retries(&url, Duration::from_millis(100));
+        assert_eq!(result, Err(QuoteError::Network(url)));
+        assert_eq!(connections.load(Ordering::SeqCst), 1 + RETRIES as usize);
+        // only problems that may go away are retried
+        let url = serve("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        let result = fetch_with_retries(&url, Duration::from_secs(5));
+        assert_eq!(result, Err(QuoteError::Status(url, 404)));
+    }
+
+    #[test]
+    fn fetch_quote_test() {
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 22\r\n\r\n{\"content\":\"A quote.\"}");
+        assert_eq!(fetch_quote(&Client::new(), &url), Ok("A quote.".into()));
+        let url =
+            serve("HTTP/1.1 200 OK\r\nContent-Length: 27\r\n\r\n{\"content\":\" A\\n  quote. \"}");
+        assert_eq!(fetch_quote(&Client::new(), &url), Ok("A quote.".into()));
+    }
+
+    #[test]
+    fn quote_source_url_test() {
+        let url = serve(
+            "HTTP/1.1 200 OK\r\nContent-Length: 29\r\n\r\n{\"text\":\"Another API.\",\"a\":1}",
+        );
+        let source = QuoteSource {
+            url,
+            timeout: Duration::from_secs(5),
+            offline: false,
+        };
+        assert_eq!(source.random_quote(), Ok("Another API.".into()));
+    }
+
+    #[test]
+    fn quote_or_fallback_status_test() {
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 22\r\n\r\n{\"content\":\"A quote.\"}");
+        let mut source = QuoteSource {
+            url,
+            timeout: Duration::from_secs(5),
+            offline: false,
+        };
+        let mut messages = Vec::new();
+        let quote = source.quote_or_fallback(|status| messages.push(status.to_string()));
+        assert_eq!(quote, "A quote.");
+        assert_eq!(messages, ["Fetching quote...", "Fetched quote."]);
+        source.url = serve("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        let mut messages = Vec::new();
+        let quote = source.quote_or_fallback(|status| messages.push(status.to_string()));
+        assert!(FALLBACK_QUOTES.contains(&quote.as_str()));
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], "Fetching quote...");
+        assert!(messages[1].ends_with("responded with status 404. Using a bundled quote instead."));
+    }
+
+    #[test]
+    fn prefetch_test() {
+        let source = QuoteSource {
+            url: serve("HTTP/1.1 200 OK\r\nContent-Length: 22\r\n\r\n{\"content\":\"A quote.\"}"),
+            timeout: Duration::from_secs(5),
+            offline: false,
+        };
+        let prefetch = source.prefetch();
+        let mut messages = Vec::new();
+        let quote = prefetch.quote_or_fallback(|status| messages.push(status.to_string()));
+        assert_eq!(quote, "A quote.");
+        assert_eq!(messages, ["Fetched quote."]);
+        let source = QuoteSource {
+            url: serve("HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n"),
+            offline: true,
+            ..source
+        };
+        let prefetch = source.prefetch();
+        assert!(prefetch.is_ready());
+        let quote = prefetch.quote_or_fallback(|_| panic!("nothing is fetched offline"));
+        assert!(FALLBACK_QUOTES.contains(&quote.as_str()));
+    }
+
+    #[test]
+    fn fetch_status_test() {
+        assert_eq!(FetchStatus::Fetching.to_string(), "Fetching quote...");
+        assert_eq!(FetchStatus::Fetched.to_string(), "Fetched quote.");
+        assert_eq!(
+            FetchStatus::Failed(&QuoteError::Network("url".into())).to_string(),
+            "Could not get quote because the url \"url\" cannot be fetched. \
+             Using a bundled quote instead."
+        );
+    }
+
+    #[test]
+    fn fetch_quote_error_test() {
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\nnot json!");
+        assert_eq!(
+            fetch_quote(&Client::new(), &url),
+            Err(QuoteError::Deserialize(url.clone()))
+        );
+        // nothing is listening on this port once the listener is dropped
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        assert_eq!(
+            fetch_quote(&Client::new(), &url),
+            Err(QuoteError::Network(url.clone()))
+        );
+        let url = serve(
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 20\r\n\r\n<html>down</html>\r\n",
+        );
+        assert_eq!(
+            fetch_quote(&Client::new(), &url),
+            Err(QuoteError::Status(url.clone(), 503))
+        );
+        assert_eq!(
+            QuoteError::Status("url".into(), 503).to_string(),
+            "Could not get quote because the url \"url\" responded with status 503."
+        );
+        assert_eq!(
+            QuoteError::Network("url".into()).to_string(),
+            "Could not get quote because the url \"url\" cannot be fetched."
+        );
+    }
+
+    #[test]
+    fn fallback_quote_test() {
+        for _ in 0..100 {
+            let quote = fallback_quote();
+            assert_ne!(quote, "");
+            assert_eq!(quote, normalize(&quote));
+        }
+        let source = QuoteSource {
+            url: QUOTE_URL.into(),
+            timeout: Duration::from_secs(5),
+            offline: true,
+        };
+        let quote = source.quote_or_fallback(|_| unreachable!());
+        assert!(FALLBACK_QUOTES.contains(&quote.as_str()));
+    }
+
+    #[test]
+    fn normalize_test() {
+        assert_eq!(normalize("a  b\nc\t d "), "a b c d");
+        assert_eq!(normalize(" \n "), "");
+    }
+
+    #[test]
+    fn read_quote_test() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "This is\na  quote\n\nfrom a file.").unwrap();
+        assert_eq!(
+            read_quote(file.path()),
+            Ok("This is a quote from a file.".into())
+        );
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        assert!(read_quote(empty.path()).unwrap_err().contains("is empty"));
+        let missing = Path::new("this/file/does/not/exist.txt");
+        assert!(read_quote(missing).unwrap_err().contains("Could not read"));
+    }
 }
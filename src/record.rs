@@ -0,0 +1,361 @@
+//! Used to log keystrokes to a file so a test can be replayed later
+use crate::{InputSource, TerminalInput};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, prelude::*};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The longest a replay sleeps before letting the test update
+const REPLAY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The first line of a record file
+/// Holds what is needed to type the same text again when replaying
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Header {
+    /// The seed the words were generated with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// The quote that was typed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+}
+
+/// One line of a record file
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct Keystroke {
+    /// Milliseconds since recording started
+    pub ms: u64,
+    /// The key that was pressed, like "a", "Backspace", or "Ctrl+Backspace"
+    pub key: String,
+}
+
+/// Get the name a key is recorded as
+/// Returns None for keys that do nothing in a test
+pub fn key_name(key: KeyEvent) -> Option<String> {
+    let name = match key.code {
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Enter => "Enter".into(),
+        KeyCode::Tab => "Tab".into(),
+        KeyCode::Esc => "Esc".into(),
+        KeyCode::Backspace => "Backspace".into(),
+        KeyCode::F(n) => format!("F{n}"),
+        _ => return None,
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        Some(format!("Ctrl+{name}"))
+    } else {
+        Some(name)
+    }
+}
+
+/// Get the key a recorded name stands for
+/// This is the reverse of `key_name`
+pub fn parse_key(name: &str) -> Option<KeyEvent> {
+    let (name, modifiers) = match name.strip_prefix("Ctrl+").filter(|rest| !rest.is_empty()) {
+        Some(rest) => (rest, KeyModifiers::CONTROL),
+        None => (name, KeyModifiers::NONE),
+    };
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(ch), None) => KeyCode::Char(ch),
+        _ => match name {
+            "Enter" => KeyCode::Enter,
+            "Tab" => KeyCode::Tab,
+            "Esc" => KeyCode::Esc,
+            "Backspace" => KeyCode::Backspace,
+            _ => KeyCode::F(name.strip_prefix('F')?.parse().ok()?),
+        },
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+/// Parse the lines of a record file
+/// Files without a header line get the default header
+pub fn parse_recording(contents: &str) -> Result<(Header, Vec<Keystroke>), String> {
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .peekable();
+    let header = match lines.peek().map(|(_, line)| serde_json::from_str(line)) {
+        Some(Ok(header)) => {
+            lines.next();
+            header
+        }
+        _ => Header::default(),
+    };
+    let keystrokes = lines
+        .map(|(i, line)| {
+            let keystroke: Keystroke = serde_json::from_str(line)
+                .map_err(|e| format!("Invalid keystroke on line {} because {e}.", i + 1))?;
+            match parse_key(&keystroke.key) {
+                Some(_) => Ok(keystroke),
+                None => Err(format!(
+                    "Invalid keystroke on line {} because \"{}\" is not a key.",
+                    i + 1,
+                    keystroke.key
+                )),
+            }
+        })
+        .collect::<Result<_, String>>()?;
+    Ok((header, keystrokes))
+}
+
+/// Read a recording made with --record
+pub fn read_recording(path: &Path) -> Result<(Header, Vec<Keystroke>), String> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        format!(
+            "Could not read replay file \"{}\" because {e}.",
+            path.display()
+        )
+    })?;
+    parse_recording(&contents)
+}
+
+/// Writes each keystroke as a line of JSON with the time since recording started
+pub struct Recorder<W: Write> {
+    start: Instant,
+    writer: W,
+}
+
+impl Recorder<File> {
+    /// Start recording to a new file, replacing it if it exists
+    pub fn create(path: &Path, header: &Header) -> Result<Self, String> {
+        File::create(path)
+            .and_then(|file| Self::new(file, header))
+            .map_err(|e| {
+                format!(
+                    "Could not create record file \"{}\" because {e}.",
+                    path.display()
+                )
+            })
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Write {header} to {writer} and start recording now
+    pub fn new(mut writer: W, header: &Header) -> io::Result<Self> {
+        serde_json::to_writer(&mut writer, header)?;
+        writeln!(writer)?;
+        Ok(Self {
+            start: Instant::now(),
+            writer,
+        })
+    }
+
+    /// Log {event} if it is a key press that happened at {now}
+    pub fn record(&mut self, event: &Event, now: Instant) -> io::Result<()> {
+        let key = match event {
+            Event::Key(key) => key_name(*key),
+            _ => None,
+        };
+        if let Some(key) = key {
+            let ms = now.saturating_duration_since(self.start).as_millis() as u64;
+            serde_json::to_writer(&mut self.writer, &Keystroke { ms, key })?;
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Play back recorded keystrokes at the time they were pressed
+/// Input is read from the terminal once the recording runs out
+pub struct ReplayInput {
+    keystrokes: VecDeque<(Duration, Event)>,
+    start: Option<Instant>,
+}
+
+impl ReplayInput {
+    /// Play {keystrokes} back {speed} times as fast as they were recorded
+    pub fn new(keystrokes: &[Keystroke], speed: f64) -> Self {
+        let keystrokes = keystrokes
+            .iter()
+            .filter_map(|keystroke| {
+                let at = Duration::from_secs_f64(keystroke.ms as f64 / 1000.0 / speed);
+                parse_key(&keystroke.key).map(|key| (at, Event::Key(key)))
+            })
+            .collect();
+        Self {
+            keystrokes,
+            start: None,
+        }
+    }
+}
+
+impl InputSource for ReplayInput {
+    fn poll(&mut self) -> crossterm::Result<Option<Event>> {
+        // the replay starts the first time input is waited for
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let at = match self.keystrokes.front() {
+            Some((at, _)) => *at,
+            None => return TerminalInput.poll(),
+        };
+        let wait = at.saturating_sub(start.elapsed());
+        if wait > REPLAY_INTERVAL {
+            thread::sleep(REPLAY_INTERVAL);
+            return Ok(None);
+        }
+        thread::sleep(wait);
+        Ok(self.keystrokes.pop_front().map(|(_, event)| event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn key_name_test() {
+        let name = |code, modifiers| key_name(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            name(KeyCode::Char('a'), KeyModifiers::NONE),
+            Some("a".into())
+        );
+        assert_eq!(
+            name(KeyCode::Char(' '), KeyModifiers::NONE),
+            Some(" ".into())
+        );
+        assert_eq!(name(KeyCode::F(2), KeyModifiers::NONE), Some("F2".into()));
+        assert_eq!(
+            name(KeyCode::Backspace, KeyModifiers::CONTROL),
+            Some("Ctrl+Backspace".into())
+        );
+        assert_eq!(name(KeyCode::Up, KeyModifiers::NONE), None);
+    }
+
+    #[test]
+    fn parse_key_test() {
+        for (code, modifiers) in [
+            (KeyCode::Char('a'), KeyModifiers::NONE),
+            (KeyCode::Char(' '), KeyModifiers::NONE),
+            (KeyCode::Char('+'), KeyModifiers::NONE),
+            (KeyCode::Char('F'), KeyModifiers::NONE),
+            (KeyCode::Enter, KeyModifiers::NONE),
+            (KeyCode::Tab, KeyModifiers::NONE),
+            (KeyCode::Esc, KeyModifiers::NONE),
+            (KeyCode::F(12), KeyModifiers::NONE),
+            (KeyCode::Backspace, KeyModifiers::CONTROL),
+        ] {
+            let key = KeyEvent::new(code, modifiers);
+            assert_eq!(parse_key(&key_name(key).unwrap()), Some(key));
+        }
+        for invalid in ["", "Up", "Fx", "Ctrl+", "Ctrl+Nope"] {
+            assert_eq!(parse_key(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parse_recording_test() {
+        let (header, keystrokes) =
+            parse_recording("{\"ms\":0,\"key\":\"a\"}\n\n{\"ms\":9,\"key\":\"Tab\"}\n").unwrap();
+        assert_eq!(header, Header::default());
+        assert_eq!(
+            keystrokes,
+            [
+                Keystroke {
+                    ms: 0,
+                    key: "a".into()
+                },
+                Keystroke {
+                    ms: 9,
+                    key: "Tab".into()
+                }
+            ]
+        );
+        let e = parse_recording("{\"ms\":0,\"key\":\"a\"}\nnot json").unwrap_err();
+        assert!(e.contains("line 2"), "{}", e);
+        let e = parse_recording("{\"ms\":0,\"key\":\"Up\"}").unwrap_err();
+        assert!(e.contains("\"Up\""), "{}", e);
+        let (header, keystrokes) =
+            parse_recording("{\"seed\":7}\n{\"ms\":3,\"key\":\"b\"}").unwrap();
+        assert_eq!(header.seed, Some(7));
+        assert_eq!(keystrokes.len(), 1);
+    }
+
+    #[test]
+    fn replay_input_test() {
+        let (_, keystrokes) = parse_recording(
+            "{\"ms\":0,\"key\":\"h\"}\n{\"ms\":200,\"key\":\"i\"}\n{\"ms\":240,\"key\":\"Esc\"}",
+        )
+        .unwrap();
+        // playing back twice as fast waits 100ms for the second key
+        let mut input = ReplayInput::new(&keystrokes, 2.0);
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while events.len() < 3 {
+            if let Some(event) = input.poll().unwrap() {
+                events.push(event);
+            }
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(120), "{:?}", elapsed);
+        assert_eq!(
+            events,
+            [
+                key(KeyCode::Char('h')),
+                key(KeyCode::Char('i')),
+                key(KeyCode::Esc)
+            ]
+        );
+    }
+
+    #[test]
+    fn recorder_test() {
+        let header = Header {
+            seed: Some(42),
+            quote: None,
+        };
+        let mut recorder = Recorder::new(Vec::new(), &header).unwrap();
+        let start = recorder.start;
+        let events = [
+            key(KeyCode::Char('h')),
+            Event::Resize(80, 24),
+            key(KeyCode::Char('i')),
+            key(KeyCode::Home),
+            key(KeyCode::Backspace),
+            key(KeyCode::Tab),
+        ];
+        for (i, event) in events.iter().enumerate() {
+            let now = start + Duration::from_millis(100 * i as u64 + 5);
+            recorder.record(event, now).unwrap();
+        }
+        let written = String::from_utf8(recorder.writer).unwrap();
+        let (read_header, keystrokes) = parse_recording(&written).unwrap();
+        assert_eq!(read_header, header);
+        let keys: Vec<&str> = keystrokes.iter().map(|k| k.key.as_str()).collect();
+        assert_eq!(keys, ["h", "i", "Backspace", "Tab"]);
+        let times: Vec<u64> = keystrokes.iter().map(|k| k.ms).collect();
+        assert_eq!(times, [5, 205, 405, 505]);
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+        let lines: Vec<&str> = written.lines().take(2).collect();
+        assert_eq!(lines, [r#"{"seed":42}"#, r#"{"ms":5,"key":"h"}"#]);
+    }
+
+    #[test]
+    fn recorder_create_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.jsonl");
+        let header = Header {
+            seed: None,
+            quote: Some("a quote".into()),
+        };
+        let mut recorder = Recorder::create(&path, &header).unwrap();
+        let now = Instant::now();
+        recorder.record(&key(KeyCode::Char('x')), now).unwrap();
+        let (read_header, keystrokes) = read_recording(&path).unwrap();
+        assert_eq!(read_header, header);
+        assert_eq!(keystrokes[0].key, "x");
+        let e = Recorder::create(&dir.path().join("missing").join("keys.jsonl"), &header);
+        assert!(e.is_err());
+    }
+}
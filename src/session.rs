@@ -0,0 +1,84 @@
+//! Contains a practice session that cycles through several drills until time runs out
+use crate::TestMode;
+use std::time::{Duration, Instant};
+
+/// The number of seconds each time drill lasts
+const DRILL_SECONDS: u64 = 30;
+/// The number of words each number drill lasts
+const DRILL_WORDS: u32 = 30;
+/// The number of drills to cycle through
+const DRILL_COUNT: usize = 3;
+
+/// Keeps track of the drill being practiced and the time left in the session
+pub struct Session {
+    length: Duration,
+    start: Instant,
+    drill: usize,
+}
+
+impl Session {
+    /// Start a session lasting a number of minutes
+    pub fn new(minutes: u64) -> Self {
+        Self {
+            length: Duration::from_secs(minutes * 60),
+            start: Instant::now(),
+            drill: 0,
+        }
+    }
+
+    /// Get the test mode of the current drill
+    /// Quote drills start without a quote so one is fetched when the test is reset
+    pub fn drill(&self) -> TestMode {
+        match self.drill % DRILL_COUNT {
+            0 => TestMode::WordCount(DRILL_WORDS),
+            1 => TestMode::TimeLimit(DRILL_SECONDS),
+            _ => TestMode::QuoteMode {
+                remaining: String::new(),
+                custom: None,
+            },
+        }
+    }
+
+    /// Move on to the next drill and get its test mode
+    pub fn next_drill(&mut self) -> TestMode {
+        self.drill += 1;
+        self.drill()
+    }
+
+    /// Get the time left in the session
+    pub fn remaining(&self) -> Duration {
+        self.length.saturating_sub(self.start.elapsed())
+    }
+
+    /// Returns true if the session has run out of time
+    pub fn expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_next_drill_test() {
+        let mut session = Session::new(10);
+        assert!(matches!(session.drill(), TestMode::WordCount(DRILL_WORDS)));
+        assert!(matches!(
+            session.next_drill(),
+            TestMode::TimeLimit(DRILL_SECONDS)
+        ));
+        assert!(matches!(session.next_drill(), TestMode::QuoteMode { .. }));
+        assert!(matches!(session.next_drill(), TestMode::WordCount(_)));
+    }
+
+    #[test]
+    fn session_expired_test() {
+        let session = Session::new(10);
+        assert!(!session.expired());
+        assert!(session.remaining() > Duration::from_secs(9 * 60));
+        let session = Session::new(0);
+        assert!(session.expired());
+        assert_eq!(session.remaining(), Duration::ZERO);
+    }
+}
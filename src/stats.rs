@@ -0,0 +1,578 @@
+//! Contains structs for keeping track of the results of finished tests
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// The least number of seconds a test can take for its wpm to be measured
+pub const MIN_ELAPSED: f32 = 0.5;
+
+/// The most decimal places metrics can be displayed with
+pub const MAX_PRECISION: usize = 4;
+
+/// The number of seconds recent wpm is measured over
+pub const RECENT_WINDOW: f32 = 10.0;
+
+/// The number of characters in a standard word used to compare words of different lengths
+const CHARS_PER_WORD: f32 = 5.0;
+
+/// Calculate words per minute
+/// Returns None if too little time has passed to measure it
+pub fn wpm(words: u32, elapsed: f32) -> Option<f32> {
+    if elapsed < MIN_ELAPSED {
+        None
+    } else {
+        Some(words as f32 / (elapsed / 60f32))
+    }
+}
+
+/// Calculate the percentage of keystrokes that were correct
+/// Returns None if nothing has been typed
+pub fn accuracy(keystrokes: u32, errors: u32) -> Option<f32> {
+    if keystrokes == 0 {
+        None
+    } else {
+        Some(keystrokes.saturating_sub(errors) as f32 / keystrokes as f32 * 100f32)
+    }
+}
+
+/// Calculate wpm from a number of characters using standard words of {CHARS_PER_WORD} characters
+/// Returns None if no time has passed
+pub fn char_wpm(chars: u32, seconds: f32) -> Option<f32> {
+    (seconds > 0f32).then(|| chars as f32 / CHARS_PER_WORD * 60f32 / seconds)
+}
+
+/// Calculate how many characters would have been typed after {elapsed} seconds at {target_wpm}
+pub fn pace_position(target_wpm: u32, elapsed: f32) -> usize {
+    (target_wpm as f32 * CHARS_PER_WORD * elapsed / 60f32) as usize
+}
+
+/// Calculate the wpm a single word was typed at, counting the space after it
+/// Returns None if the word took no time
+pub fn word_wpm(word: &str, duration: Duration) -> Option<f32> {
+    char_wpm(word.chars().count() as u32 + 1, duration.as_secs_f32())
+}
+
+/// Get the {count} words with the lowest mean wpm from slowest to fastest
+pub fn slowest_words(times: &[(String, Duration)], count: usize) -> Vec<(&str, f32)> {
+    let mut totals: HashMap<&str, (f32, u32)> = HashMap::new();
+    for (word, duration) in times {
+        if let Some(wpm) = word_wpm(word, *duration) {
+            let total = totals.entry(word).or_default();
+            total.0 += wpm;
+            total.1 += 1;
+        }
+    }
+    let mut words: Vec<(&str, f32)> = totals
+        .into_iter()
+        .map(|(word, (sum, n))| (word, sum / n as f32))
+        .collect();
+    words.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(b.0)));
+    words.truncate(count);
+    words
+}
+
+/// Format a metric with the given width and precision
+/// Shows "--" if the metric cannot be measured
+pub fn format_metric(value: Option<f32>, width: usize, precision: usize) -> String {
+    match value.filter(|value| value.is_finite()) {
+        Some(value) => format!("{value:width$.precision$}"),
+        None => format!("{:>width$}", "--"),
+    }
+}
+
+/// Word counts sampled during a test for measuring wpm over the last few seconds
+#[derive(Debug, Default)]
+pub struct Recent {
+    /// Seconds since the test started and the words typed at that time
+    samples: VecDeque<(f32, u32)>,
+}
+
+impl Recent {
+    /// Record the number of words typed after the given number of seconds
+    pub fn record(&mut self, elapsed: f32, words: u32) {
+        self.samples.push_back((elapsed, words));
+        // keep one sample from before the window so the whole window is measured
+        while self.samples.len() > 1 && self.samples[1].0 <= elapsed - RECENT_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Calculate words per minute over the last `RECENT_WINDOW` seconds
+    /// Returns None if too little time has been recorded to measure it
+    pub fn wpm(&self) -> Option<f32> {
+        let (start, start_words) = *self.samples.front()?;
+        let (end, end_words) = *self.samples.back()?;
+        wpm(end_words.saturating_sub(start_words), end - start)
+    }
+
+    /// Forget all recorded samples
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// The result of a single finished test
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestResult {
+    /// The mode the test was run in
+    pub mode: String,
+    /// The number of correctly typed words
+    pub words: u32,
+    /// The number of typed words whether or not they were correct
+    pub raw_words: u32,
+    /// The number of seconds the test took
+    pub elapsed: f32,
+    /// The number of characters typed
+    pub keystrokes: u32,
+    /// The number of incorrect characters typed
+    pub errors: u32,
+}
+
+impl TestResult {
+    /// Calculate the words per minute of the test
+    pub fn wpm(&self) -> Option<f32> {
+        wpm(self.words, self.elapsed)
+    }
+
+    /// Calculate the words per minute of the test including incorrect words
+    pub fn raw_wpm(&self) -> Option<f32> {
+        wpm(self.raw_words, self.elapsed)
+    }
+
+    /// Calculate the percentage of keystrokes that were correct
+    pub fn accuracy(&self) -> Option<f32> {
+        accuracy(self.keystrokes, self.errors)
+    }
+}
+
+/// The final result of a run in a form that can be read by other programs
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Summary {
+    /// The mode the test was run in
+    pub mode: String,
+    /// The number of correctly typed words
+    pub words: u32,
+    /// The number of typed words whether or not they were correct
+    pub raw_words: u32,
+    /// The number of seconds the test took
+    pub elapsed_secs: f32,
+    /// Words per minute, or None if the test was too fast to measure
+    pub wpm: Option<f32>,
+    /// Words per minute including incorrect words
+    pub raw_wpm: Option<f32>,
+    /// The percentage of keystrokes that were correct
+    pub accuracy: Option<f32>,
+}
+
+impl From<&TestResult> for Summary {
+    fn from(result: &TestResult) -> Self {
+        Self {
+            mode: result.mode.clone(),
+            words: result.words,
+            raw_words: result.raw_words,
+            elapsed_secs: result.elapsed,
+            wpm: result.wpm(),
+            raw_wpm: result.raw_wpm(),
+            accuracy: result.accuracy(),
+        }
+    }
+}
+
+/// Calculate the mean of some values
+/// Returns None if there are no values
+fn mean(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0f32, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// The mean wpm and accuracy of several results
+#[derive(Debug, PartialEq)]
+pub struct Average {
+    /// The mean wpm of the results that could be measured
+    pub wpm: Option<f32>,
+    /// The mean accuracy of the results that had keystrokes
+    pub accuracy: Option<f32>,
+}
+
+/// Average the wpm and accuracy of each result
+pub fn average(results: &[TestResult]) -> Average {
+    Average {
+        wpm: mean(results.iter().filter_map(TestResult::wpm)),
+        accuracy: mean(results.iter().filter_map(TestResult::accuracy)),
+    }
+}
+
+/// Calculate the change in wpm from the first result to the last result
+/// Returns None if there are less than two results or either is too fast to measure
+pub fn improvement(results: &[TestResult]) -> Option<f32> {
+    match results {
+        [first, .., last] => Some(last.wpm()? - first.wpm()?),
+        _ => None,
+    }
+}
+
+/// Compare the mean wpm of the newer half of the results to the older half
+/// Returns None if either half has no result that can be measured
+pub fn trend(results: &[TestResult]) -> Option<f32> {
+    let (older, newer) = results.split_at(results.len() / 2);
+    let wpm = |results: &[TestResult]| mean(results.iter().filter_map(TestResult::wpm));
+    Some(wpm(newer)? - wpm(older)?)
+}
+
+/// Calculate how steady the typing speed was as a percentage from wpm samples
+/// 100% is perfectly steady and it falls as the coefficient of variation rises
+/// Returns None if there are less than two samples or nothing was typed
+pub fn consistency(samples: &[f32]) -> Option<f32> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let mean = mean(samples.iter().copied())?;
+    if mean <= 0f32 {
+        return None;
+    }
+    let variance = self::mean(samples.iter().map(|s| (s - mean).powi(2)))?;
+    Some((100f32 * (1f32 - variance.sqrt() / mean)).max(0f32))
+}
+
+/// The bars of a sparkline from lowest to highest
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draw samples as a line of bars scaled between the lowest and highest sample
+/// Samples that are all the same are drawn at half height
+pub fn sparkline(samples: &[f32]) -> String {
+    let min = samples.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = samples.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let top = SPARKS.len() - 1;
+    samples
+        .iter()
+        .map(|sample| {
+            let level = if max > min {
+                ((sample - min) / (max - min) * top as f32).round() as usize
+            } else {
+                top / 2
+            };
+            SPARKS[level.min(top)]
+        })
+        .collect()
+}
+
+/// Combine results with the same mode into one result per mode
+/// The modes are kept in the order they first appear
+pub fn aggregate(results: &[TestResult]) -> Vec<TestResult> {
+    let mut combined: Vec<TestResult> = Vec::new();
+    for result in results {
+        match combined.iter_mut().find(|r| r.mode == result.mode) {
+            Some(total) => {
+                total.words += result.words;
+                total.raw_words += result.raw_words;
+                total.elapsed += result.elapsed;
+                total.keystrokes += result.keystrokes;
+                total.errors += result.errors;
+            }
+            None => combined.push(result.clone()),
+        }
+    }
+    combined
+}
+
+/// Combine all results into one result with the given mode
+pub fn total(results: &[TestResult], mode: &str) -> TestResult {
+    TestResult {
+        mode: mode.into(),
+        words: results.iter().map(|r| r.words).sum(),
+        raw_words: results.iter().map(|r| r.raw_words).sum(),
+        elapsed: results.iter().map(|r| r.elapsed).sum(),
+        keystrokes: results.iter().map(|r| r.keystrokes).sum(),
+        errors: results.iter().map(|r| r.errors).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(words: u32, elapsed: f32) -> TestResult {
+        TestResult {
+            mode: "mode".into(),
+            words,
+            raw_words: words,
+            elapsed,
+            keystrokes: 0,
+            errors: 0,
+        }
+    }
+
+    fn result_with_mode(mode: &str, words: u32, elapsed: f32) -> TestResult {
+        TestResult {
+            mode: mode.into(),
+            ..result(words, elapsed)
+        }
+    }
+
+    #[test]
+    fn wpm_test() {
+        assert_eq!(wpm(30, 60.0), Some(30.0));
+        assert_eq!(wpm(0, 0.0), None);
+        assert_eq!(wpm(5, 30.0), Some(10.0));
+        assert!(wpm(1, MIN_ELAPSED).is_some());
+    }
+
+    #[test]
+    fn format_metric_test() {
+        assert_eq!(format_metric(wpm(0, 0.0), 6, 2), "    --");
+        assert_eq!(format_metric(Some(f32::INFINITY), 6, 2), "    --");
+        assert_eq!(format_metric(Some(f32::NAN), 4, 0), "  --");
+        assert_eq!(format_metric(wpm(5, 30.0), 6, 2), " 10.00");
+        assert_eq!(format_metric(Some(0.0), 6, 2), "  0.00");
+    }
+
+    #[test]
+    fn recent_test() {
+        let mut recent = Recent::default();
+        assert_eq!(recent.wpm(), None);
+        recent.record(0.0, 0);
+        assert_eq!(recent.wpm(), None);
+        recent.record(6.0, 3);
+        assert_eq!(recent.wpm(), Some(30.0));
+        recent.record(12.0, 6);
+        recent.record(18.0, 18);
+        // the sample at 0 seconds is dropped but the one at 6 seconds is kept
+        assert_eq!(recent.wpm(), Some(75.0));
+        recent.clear();
+        assert_eq!(recent.wpm(), None);
+    }
+
+    #[test]
+    fn accuracy_test() {
+        assert_eq!(accuracy(0, 0), None);
+        assert_eq!(accuracy(10, 0), Some(100.0));
+        assert_eq!(accuracy(10, 1), Some(90.0));
+        assert_eq!(accuracy(4, 3), Some(25.0));
+        assert_eq!(accuracy(4, 4), Some(0.0));
+    }
+
+    #[test]
+    fn test_result_raw_wpm_test() {
+        let result = TestResult {
+            raw_words: 45,
+            ..result(30, 60.0)
+        };
+        assert_eq!(result.wpm(), Some(30.0));
+        assert_eq!(result.raw_wpm(), Some(45.0));
+    }
+
+    #[test]
+    fn test_result_accuracy_test() {
+        let result = TestResult {
+            keystrokes: 200,
+            errors: 10,
+            ..result(40, 60.0)
+        };
+        assert_eq!(result.accuracy(), Some(95.0));
+        let results = [result.clone(), result];
+        assert_eq!(total(&results, "all").accuracy(), Some(95.0));
+    }
+
+    #[test]
+    fn test_result_wpm_test() {
+        assert_eq!(result(30, 60.0).wpm(), Some(30.0));
+        assert_eq!(result(30, 30.0).wpm(), Some(60.0));
+        assert_eq!(result(0, 10.0).wpm(), Some(0.0));
+    }
+
+    #[test]
+    fn instant_completion_test() {
+        // a whole test typed by a script within a single tick
+        let instant = result(30, 0.001);
+        assert_eq!(instant.wpm(), None);
+        assert_eq!(improvement(&[instant, result(30, 60.0)]), None);
+    }
+
+    #[test]
+    fn summary_test() {
+        let finished = TestResult {
+            raw_words: 40,
+            keystrokes: 100,
+            errors: 25,
+            ..result(30, 60.0)
+        };
+        let json = serde_json::to_value(Summary::from(&finished)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "mode": "mode",
+                "words": 30,
+                "raw_words": 40,
+                "elapsed_secs": 60.0,
+                "wpm": 30.0,
+                "raw_wpm": 40.0,
+                "accuracy": 75.0,
+            })
+        );
+        let json = serde_json::to_value(Summary::from(&result(30, 0.1))).unwrap();
+        assert!(json["wpm"].is_null());
+    }
+
+    #[test]
+    fn word_wpm_test() {
+        // "four" and a space is one standard word
+        assert_eq!(word_wpm("four", Duration::from_secs(1)), Some(60.0));
+        assert_eq!(word_wpm("nine char", Duration::from_secs(2)), Some(60.0));
+        assert_eq!(word_wpm("four", Duration::from_millis(500)), Some(120.0));
+        assert_eq!(word_wpm("four", Duration::ZERO), None);
+    }
+
+    #[test]
+    fn pace_position_test() {
+        assert_eq!(pace_position(60, 0.0), 0);
+        // 60 wpm is 5 characters a second
+        assert_eq!(pace_position(60, 1.0), 5);
+        assert_eq!(pace_position(60, 1.1), 5);
+        assert_eq!(pace_position(60, 1.2), 6);
+        assert_eq!(pace_position(120, 30.0), 300);
+        assert_eq!(pace_position(0, 30.0), 0);
+    }
+
+    #[test]
+    fn consistency_test() {
+        assert_eq!(consistency(&[60.0, 60.0, 60.0, 60.0]), Some(100.0));
+        // a standard deviation of 10 is a sixth of the mean
+        let steady = consistency(&[50.0, 70.0, 50.0, 70.0]).unwrap();
+        assert!((steady - 83.33333).abs() < 0.001);
+        // deviating more than the mean is as inconsistent as possible
+        assert_eq!(consistency(&[0.0, 0.0, 0.0, 200.0]), Some(0.0));
+        assert!(consistency(&[20.0, 100.0]).unwrap() < steady);
+        assert_eq!(consistency(&[60.0]), None);
+        assert_eq!(consistency(&[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn sparkline_test() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[60.0]), "▄");
+        assert_eq!(sparkline(&[50.0, 50.0]), "▄▄");
+        assert_eq!(sparkline(&[0.0, 70.0]), "▁█");
+        assert_eq!(
+            sparkline(&[10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0]),
+            "▁▂▃▄▅▆▇█"
+        );
+        assert_eq!(sparkline(&[80.0, 40.0, 10.0]), "█▄▁");
+    }
+
+    #[test]
+    fn slowest_words_test() {
+        let secs = |word: &str, secs| (word.to_string(), Duration::from_secs(secs));
+        let times = [
+            secs("four", 1),
+            secs("slow", 4),
+            secs("four", 3),
+            secs("okay", 2),
+            ("zero".into(), Duration::ZERO),
+        ];
+        assert_eq!(
+            slowest_words(&times, 2),
+            vec![("slow", 15.0), ("okay", 30.0)]
+        );
+        // four averages 60 and 20 wpm
+        assert_eq!(slowest_words(&times, 5)[2], ("four", 40.0));
+        assert_eq!(slowest_words(&times, 5).len(), 3);
+        assert!(slowest_words(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn average_test() {
+        assert_eq!(
+            average(&[]),
+            Average {
+                wpm: None,
+                accuracy: None
+            }
+        );
+        let results = [
+            TestResult {
+                keystrokes: 100,
+                errors: 10,
+                ..result(30, 60.0)
+            },
+            TestResult {
+                keystrokes: 100,
+                errors: 20,
+                ..result(60, 60.0)
+            },
+            // too fast to measure and nothing typed so it is left out
+            result(30, 0.1),
+            TestResult {
+                keystrokes: 50,
+                errors: 0,
+                ..result(15, 20.0)
+            },
+        ];
+        assert_eq!(
+            average(&results),
+            Average {
+                wpm: Some(45.0),
+                accuracy: Some(90.0)
+            }
+        );
+    }
+
+    #[test]
+    fn improvement_test() {
+        assert_eq!(improvement(&[]), None);
+        assert_eq!(improvement(&[result(10, 60.0)]), None);
+        assert_eq!(
+            improvement(&[result(10, 60.0), result(5, 60.0), result(20, 60.0)]),
+            Some(10.0)
+        );
+        assert_eq!(
+            improvement(&[result(20, 60.0), result(10, 60.0)]),
+            Some(-10.0)
+        );
+    }
+
+    #[test]
+    fn trend_test() {
+        assert_eq!(trend(&[]), None);
+        assert_eq!(trend(&[result(10, 60.0)]), None);
+        assert_eq!(trend(&[result(10, 60.0), result(20, 60.0)]), Some(10.0));
+        // the newer half gets the extra result when there is an odd number
+        assert_eq!(
+            trend(&[
+                result(30, 60.0),
+                result(10, 60.0),
+                result(20, 60.0),
+                result(20, 60.0),
+                result(5, 60.0)
+            ]),
+            Some(-5.0)
+        );
+        assert_eq!(trend(&[result(10, 0.1), result(20, 60.0)]), None);
+    }
+
+    #[test]
+    fn aggregate_test() {
+        assert_eq!(aggregate(&[]), vec![]);
+        let results = [
+            result_with_mode("words", 10, 20.0),
+            result_with_mode("quote", 5, 10.0),
+            result_with_mode("words", 20, 40.0),
+        ];
+        assert_eq!(
+            aggregate(&results),
+            vec![
+                result_with_mode("words", 30, 60.0),
+                result_with_mode("quote", 5, 10.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn total_test() {
+        let results = [
+            result_with_mode("words", 10, 20.0),
+            result_with_mode("quote", 5, 10.0),
+        ];
+        assert_eq!(total(&results, "all"), result_with_mode("all", 15, 30.0));
+        assert_eq!(total(&[], "none"), result_with_mode("none", 0, 0.0));
+    }
+}
@@ -0,0 +1,255 @@
+//! Contains the conditions that can end a typing test
+use crate::TestMode;
+use std::time::Duration;
+
+/// The state of a test that stop conditions are checked against
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    /// The number of correctly typed words
+    pub words: u32,
+    /// The time since the first keystroke if there has been one
+    pub elapsed: Option<Duration>,
+    /// Whether or not all of the text has been typed
+    pub text_done: bool,
+    /// Whether or not the practice session has run out of time
+    pub session_expired: bool,
+    /// The number of incorrect characters typed
+    pub errors: u32,
+    /// Whether or not the current line has no uncorrected mistakes
+    pub line_clean: bool,
+}
+
+/// Something that can end a typing test
+pub trait StopCondition {
+    /// Returns true if the test should stop
+    fn reached(&self, progress: &Progress) -> bool;
+
+    /// Describe why the test stopped
+    fn reason(&self) -> &'static str;
+}
+
+/// Stop after a certain number of correctly typed words
+pub struct WordLimit(pub u32);
+
+impl StopCondition for WordLimit {
+    fn reached(&self, progress: &Progress) -> bool {
+        progress.words >= self.0
+    }
+
+    fn reason(&self) -> &'static str {
+        "reached word count"
+    }
+}
+
+/// Stop after a certain number of correctly typed words once the current line has no mistakes
+pub struct CleanWordLimit(pub u32);
+
+impl StopCondition for CleanWordLimit {
+    fn reached(&self, progress: &Progress) -> bool {
+        progress.words >= self.0 && progress.line_clean
+    }
+
+    fn reason(&self) -> &'static str {
+        "reached word count"
+    }
+}
+
+/// Get the condition that stops a test after {words} words
+/// The mistakes on the current line must be fixed first if {clean} is true
+fn word_limit(words: u32, clean: bool) -> Box<dyn StopCondition> {
+    if clean {
+        Box::new(CleanWordLimit(words))
+    } else {
+        Box::new(WordLimit(words))
+    }
+}
+
+/// Stop after a certain amount of time has passed since the first keystroke
+pub struct TimeLimit(pub Duration);
+
+impl StopCondition for TimeLimit {
+    fn reached(&self, progress: &Progress) -> bool {
+        progress.elapsed.is_some_and(|elapsed| elapsed >= self.0)
+    }
+
+    fn reason(&self) -> &'static str {
+        "time expired"
+    }
+}
+
+/// Stop once all of the text has been typed
+pub struct TextDone;
+
+impl StopCondition for TextDone {
+    fn reached(&self, progress: &Progress) -> bool {
+        progress.text_done
+    }
+
+    fn reason(&self) -> &'static str {
+        "finished the quote"
+    }
+}
+
+/// Stop once the practice session runs out of time
+pub struct SessionExpired;
+
+impl StopCondition for SessionExpired {
+    fn reached(&self, progress: &Progress) -> bool {
+        progress.session_expired
+    }
+
+    fn reason(&self) -> &'static str {
+        "session time expired"
+    }
+}
+
+/// Stop as soon as a character is typed incorrectly
+pub struct FirstMistake;
+
+impl StopCondition for FirstMistake {
+    fn reached(&self, progress: &Progress) -> bool {
+        progress.errors > 0
+    }
+
+    fn reason(&self) -> &'static str {
+        "made a mistake"
+    }
+}
+
+/// Get the conditions that end a test in the given mode
+/// Word limits wait for the current line to have no mistakes if {clean} is true
+pub fn for_mode(mode: &TestMode, clean: bool) -> Vec<Box<dyn StopCondition>> {
+    match mode {
+        TestMode::WordCount(words) => vec![word_limit(*words, clean)],
+        TestMode::TimeLimit(seconds) => {
+            vec![Box::new(TimeLimit(Duration::from_secs(*seconds)))]
+        }
+        TestMode::WordsOrTime { words, seconds } => vec![
+            word_limit(*words, clean),
+            Box::new(TimeLimit(Duration::from_secs(*seconds))),
+        ],
+        TestMode::QuoteMode { .. } => vec![Box::new(TextDone)],
+        TestMode::Zen => Vec::new(),
+    }
+}
+
+/// Get the reason of the first condition that has been reached
+pub fn check(conditions: &[Box<dyn StopCondition>], progress: &Progress) -> Option<&'static str> {
+    conditions
+        .iter()
+        .find(|condition| condition.reached(progress))
+        .map(|condition| condition.reason())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_limit_test() {
+        let condition = WordLimit(10);
+        let mut progress = Progress::default();
+        assert!(!condition.reached(&progress));
+        progress.words = 10;
+        assert!(condition.reached(&progress));
+    }
+
+    #[test]
+    fn clean_word_limit_test() {
+        let condition = CleanWordLimit(10);
+        let mut progress = Progress {
+            words: 10,
+            ..Progress::default()
+        };
+        // the count is reached but the line still has a mistake
+        assert!(!condition.reached(&progress));
+        progress.line_clean = true;
+        assert!(condition.reached(&progress));
+        progress.words = 9;
+        assert!(!condition.reached(&progress));
+    }
+
+    #[test]
+    fn time_limit_test() {
+        let condition = TimeLimit(Duration::from_secs(30));
+        let mut progress = Progress::default();
+        assert!(!condition.reached(&progress));
+        progress.elapsed = Some(Duration::from_secs(29));
+        assert!(!condition.reached(&progress));
+        progress.elapsed = Some(Duration::from_secs(30));
+        assert!(condition.reached(&progress));
+    }
+
+    #[test]
+    fn first_mistake_test() {
+        let mut progress = Progress::default();
+        assert!(!FirstMistake.reached(&progress));
+        progress.errors = 1;
+        assert!(FirstMistake.reached(&progress));
+        assert_eq!(FirstMistake.reason(), "made a mistake");
+    }
+
+    #[test]
+    fn check_test() {
+        let conditions: Vec<Box<dyn StopCondition>> = vec![
+            Box::new(WordLimit(10)),
+            Box::new(TimeLimit(Duration::from_secs(30))),
+            Box::new(SessionExpired),
+        ];
+        let mut progress = Progress::default();
+        assert_eq!(check(&conditions, &progress), None);
+        progress.session_expired = true;
+        assert_eq!(check(&conditions, &progress), Some("session time expired"));
+        progress.elapsed = Some(Duration::from_secs(31));
+        assert_eq!(check(&conditions, &progress), Some("time expired"));
+        progress.words = 10;
+        assert_eq!(check(&conditions, &progress), Some("reached word count"));
+    }
+
+    #[test]
+    fn for_mode_test() {
+        let progress = Progress {
+            words: 5,
+            elapsed: Some(Duration::from_secs(5)),
+            text_done: true,
+            session_expired: false,
+            errors: 0,
+            line_clean: false,
+        };
+        let reason = |mode| check(&for_mode(&mode, false), &progress);
+        assert_eq!(reason(TestMode::WordCount(10)), None);
+        assert_eq!(reason(TestMode::WordCount(5)), Some("reached word count"));
+        assert_eq!(reason(TestMode::TimeLimit(5)), Some("time expired"));
+        let quote = TestMode::QuoteMode {
+            remaining: String::new(),
+            custom: None,
+        };
+        assert_eq!(reason(quote), Some("finished the quote"));
+        assert_eq!(
+            check(&for_mode(&TestMode::WordCount(5), true), &progress),
+            None
+        );
+    }
+
+    #[test]
+    fn words_or_time_test() {
+        let conditions = for_mode(
+            &TestMode::WordsOrTime {
+                words: 50,
+                seconds: 60,
+            },
+            false,
+        );
+        let progress = |words, seconds| Progress {
+            words,
+            elapsed: Some(Duration::from_secs(seconds)),
+            ..Progress::default()
+        };
+        assert_eq!(check(&conditions, &progress(49, 59)), None);
+        assert_eq!(
+            check(&conditions, &progress(50, 30)),
+            Some("reached word count")
+        );
+        assert_eq!(check(&conditions, &progress(20, 60)), Some("time expired"));
+    }
+}
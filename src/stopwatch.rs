@@ -0,0 +1,99 @@
+//! Contains a stopwatch for timing tests that leaves out time spent paused
+use std::time::{Duration, Instant};
+
+/// Measures time passed while it is not paused
+#[derive(Clone, Copy, Debug)]
+pub struct Stopwatch {
+    /// Time passed before the last pause
+    before: Duration,
+    /// When the stopwatch was last started or resumed, None if it is paused
+    since: Option<Instant>,
+}
+
+impl Stopwatch {
+    /// Create a stopwatch that starts running now
+    pub fn start() -> Self {
+        Self::start_at(Instant::now())
+    }
+
+    fn start_at(now: Instant) -> Self {
+        Self {
+            before: Duration::ZERO,
+            since: Some(now),
+        }
+    }
+
+    /// Whether or not the stopwatch is paused
+    pub fn paused(&self) -> bool {
+        self.since.is_none()
+    }
+
+    /// Stop counting time until resumed
+    pub fn pause(&mut self) {
+        self.pause_at(Instant::now());
+    }
+
+    fn pause_at(&mut self, now: Instant) {
+        if let Some(since) = self.since.take() {
+            self.before += now.saturating_duration_since(since);
+        }
+    }
+
+    /// Start counting time again after being paused
+    pub fn resume(&mut self) {
+        self.resume_at(Instant::now());
+    }
+
+    fn resume_at(&mut self, now: Instant) {
+        self.since.get_or_insert(now);
+    }
+
+    /// Get the time passed while not paused
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        self.before
+            + self
+                .since
+                .map_or(Duration::ZERO, |since| now.saturating_duration_since(since))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopwatch_test() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut stopwatch = Stopwatch::start_at(start);
+        assert!(!stopwatch.paused());
+        assert_eq!(stopwatch.elapsed_at(at(3)), Duration::from_secs(3));
+        stopwatch.pause_at(at(5));
+        assert!(stopwatch.paused());
+        assert_eq!(stopwatch.elapsed_at(at(100)), Duration::from_secs(5));
+        stopwatch.resume_at(at(10));
+        assert_eq!(stopwatch.elapsed_at(at(12)), Duration::from_secs(7));
+    }
+
+    #[test]
+    fn stopwatch_cycles_test() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut stopwatch = Stopwatch::start_at(start);
+        for cycle in 0..3 {
+            // run for 2 seconds then pause for 8 seconds
+            stopwatch.pause_at(at(cycle * 10 + 2));
+            stopwatch.resume_at(at(cycle * 10 + 10));
+        }
+        assert_eq!(stopwatch.elapsed_at(at(31)), Duration::from_secs(7));
+        // pausing or resuming twice does nothing
+        stopwatch.resume_at(at(40));
+        stopwatch.pause_at(at(41));
+        stopwatch.pause_at(at(50));
+        assert_eq!(stopwatch.elapsed_at(at(60)), Duration::from_secs(17));
+    }
+}
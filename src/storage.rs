@@ -0,0 +1,75 @@
+//! Used to persist data between sessions in the user's data directory
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Get the path of a file in the application's data directory
+pub fn path(name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("typing_test").join(name))
+}
+
+/// Read a value from a file in the data directory
+/// Returns the default value if there is no data directory or the file does not exist yet
+/// Returns a message describing the problem if the file cannot be read or is invalid
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> Result<T, String> {
+    match path(name) {
+        Some(path) => load_from(&path),
+        None => Ok(T::default()),
+    }
+}
+
+/// Read a value from a file, or the default value if the file does not exist
+fn load_from<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => {
+            return Err(format!(
+                "Could not read \"{}\" because {e}.",
+                path.display()
+            ))
+        }
+    };
+    serde_json::from_str(&contents).map_err(|e| {
+        format!(
+            "Could not load \"{}\" because {e}. Please fix or remove the file.",
+            path.display()
+        )
+    })
+}
+
+/// Write a value to a file in the data directory, creating the directory if needed
+pub fn save<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    let path = path(name).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "Could not find a data directory")
+    })?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn load_from_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        // a file that does not exist yet is the same as no data
+        assert_eq!(load_from::<HashMap<String, u32>>(&path), Ok(HashMap::new()));
+        fs::write(&path, r#"{"rust":3}"#).unwrap();
+        let loaded: HashMap<String, u32> = load_from(&path).unwrap();
+        assert_eq!(loaded["rust"], 3);
+        // a corrupt file is an error so it is not overwritten with nothing
+        fs::write(&path, r#"{"rust":3"#).unwrap();
+        let e = load_from::<HashMap<String, u32>>(&path).unwrap_err();
+        assert!(e.contains("Please fix or remove"), "{}", e);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"rust":3"#);
+        let e = load_from::<HashMap<String, u32>>(dir.path()).unwrap_err();
+        assert!(e.contains("Could not read"), "{}", e);
+    }
+}
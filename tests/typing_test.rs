@@ -0,0 +1,144 @@
+//! Drive typing tests through the library without a terminal
+use std::io::{self, Write};
+use std::time::Duration;
+use typing_test::{parse_args, Action, ScriptedInput, TypingTest};
+
+/// Create a test from command-line style arguments
+fn typing_test(args: &[&str]) -> TypingTest {
+    let args = parse_args(["typing_test"].iter().chain(args)).unwrap();
+    TypingTest::new(args).unwrap()
+}
+
+/// Send each character of {text} to the test as a keystroke
+fn type_text(test: &mut TypingTest, text: &str) {
+    for ch in text.chars() {
+        test.update(Action::Type(ch)).unwrap();
+    }
+}
+
+/// Type whatever is expected next until the test stops
+fn type_until_stopped(test: &mut TypingTest) -> &'static str {
+    loop {
+        if let Some(reason) = test.check_stop() {
+            return reason;
+        }
+        let ch = test.line().next_expected().unwrap_or(' ');
+        test.update(Action::Type(ch)).unwrap();
+    }
+}
+
+#[test]
+fn word_count_test() {
+    let mut test = typing_test(&["-n", "12", "--seed", "3"]);
+    assert_eq!(test.summary(), None);
+    assert_eq!(type_until_stopped(&mut test), "reached word count");
+    assert_eq!(test.word_count(), 12);
+    let summary = test.summary().unwrap();
+    assert_eq!(summary.mode, "12 words");
+    assert_eq!(summary.words, 12);
+    assert_eq!(summary.accuracy, Some(100.0));
+}
+
+#[test]
+fn mistakes_test() {
+    let mut test = typing_test(&["-q", "-c", "the quick fox"]);
+    type_text(&mut test, "the quack");
+    assert_eq!(test.check_stop(), None);
+    assert_eq!(test.word_count(), 1);
+    assert_eq!(test.raw_word_count(), 2);
+    test.update(Action::BackspaceWord).unwrap();
+    type_text(&mut test, "quick fox");
+    assert_eq!(test.check_stop(), Some("finished the quote"));
+    assert_eq!(test.word_count(), 3);
+    // 18 keystrokes with one mistake
+    assert_eq!(test.keystrokes(), (18, 1));
+    let summary = test.summary().unwrap();
+    assert_eq!(summary.mode, "quote");
+    assert_eq!(summary.accuracy, test.accuracy());
+}
+
+#[test]
+fn scripted_input_test() {
+    let quote = "The quick brown fox jumps over the lazy dog.";
+    let mut test = typing_test(&["-q", "-c", quote]);
+    let delay = Duration::from_millis(20);
+    test.set_input(ScriptedInput::from_text(quote).with_delay(delay));
+    test.set_screen(io::sink(), (80, 24));
+    test.run_attempts().unwrap();
+    let results = test.results();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].words, 9);
+    assert_eq!(results[0].keystrokes, quote.len() as u32);
+    assert_eq!(results[0].accuracy(), Some(100.0));
+    // the timer starts on the first key so at least 43 delays pass
+    let fastest = 9.0 / (43.0 * delay.as_secs_f32() / 60.0);
+    assert!(results[0]
+        .wpm()
+        .is_some_and(|wpm| wpm > 0.0 && wpm <= fastest));
+}
+
+#[test]
+fn scripted_input_quit_test() {
+    // the script quits once it runs out so the test does not wait forever
+    let mut test = typing_test(&["-n", "50"]);
+    test.set_input(ScriptedInput::from_text("not enough"));
+    test.set_screen(io::sink(), (80, 24));
+    test.run_attempts().unwrap();
+    assert_eq!(test.results().len(), 1);
+    assert!(test.results()[0].words < 50);
+}
+
+#[test]
+fn quit_test() {
+    let mut test = typing_test(&["-t", "60"]);
+    type_text(&mut test, "x");
+    test.update(Action::Quit).unwrap();
+    assert_eq!(test.check_stop(), None);
+    assert_eq!(test.word_count(), 0);
+    assert_eq!(test.accuracy(), Some(0.0));
+    assert_eq!(test.test_mode().to_string(), "60 seconds");
+}
+
+#[test]
+fn replay_test() {
+    // "the quack" is fixed with a backspace before finishing the quote
+    let keys = [
+        "t",
+        "h",
+        "e",
+        " ",
+        "q",
+        "u",
+        "a",
+        "Backspace",
+        "i",
+        "c",
+        "k",
+        " ",
+        "f",
+        "o",
+        "x",
+    ];
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    for (i, key) in keys.iter().enumerate() {
+        writeln!(file, r#"{{"ms":{},"key":"{}"}}"#, i * 20, key).unwrap();
+    }
+    let path = file.path().to_str().unwrap();
+    let args = [
+        "-q",
+        "-c",
+        "the quick fox",
+        "--replay",
+        path,
+        "--replay-speed",
+        "4",
+    ];
+    let mut test = typing_test(&args);
+    test.set_screen(io::sink(), (80, 24));
+    test.run_attempts().unwrap();
+    let results = test.results();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].words, 3);
+    assert_eq!(results[0].keystrokes, 14);
+    assert_eq!(results[0].errors, 1);
+}